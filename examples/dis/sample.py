@@ -0,0 +1,54 @@
+import os
+from collections import namedtuple as nt
+
+
+def scale(xs, factor=2, *, offset=0.5):
+    out = []
+    for x in xs:
+        if x > 10 and x not in {7}:
+            continue
+        out.append(x * factor + offset)
+    return out
+
+
+def counter():
+    count = 0
+
+    def bump(step=1):
+        nonlocal count
+        count += step
+        return count
+
+    return bump
+
+
+class Point:
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+    def __repr__(self):
+        return f"Point({self.x!r}, {self.y:>4})"
+
+
+def safe_div(a, b):
+    try:
+        return a / b
+    except ZeroDivisionError as e:
+        print("oops", e)
+        return None
+    finally:
+        a = b'\x00raw\'"'
+
+
+while os.environ.get("X") is None:
+    break
+label = 'it\'s' if -1e-07 < 1.5e20 else (1, (2.0, 3j), None, ...)
+
+from os.path import *
+from .. import sibling
+
+
+def lazy():
+    from .util import helper
+    return helper
@@ -0,0 +1,6 @@
+count: int = 0
+
+
+class Point:
+    x: int = 0
+    y: int
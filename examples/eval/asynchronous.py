@@ -0,0 +1,11 @@
+async def total(items):
+    n = 0
+    async for item in items:
+        n += await item
+    return n
+
+
+async def countdown(n):
+    while n:
+        yield n
+        n -= 1
@@ -0,0 +1,10 @@
+def doubled(args):
+    return [i * 2 for i in args]
+
+
+def odd(args):
+    return {i for i in args if i % 2}
+
+
+def inverted(pairs):
+    return {v: k for k, v in pairs}
@@ -0,0 +1,15 @@
+import os
+
+
+def deletes(a, b):
+    global g
+    c = a
+
+    def inner():
+        return c
+
+    del a, g, c, b.attr, b[0]
+    return inner
+
+
+del os
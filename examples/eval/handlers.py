@@ -0,0 +1,22 @@
+def reraised(f):
+    try:
+        try:
+            x = f()
+        except ValueError as e:
+            raise
+    except KeyError as k:
+        return k
+    return x
+
+
+def reraised_in_loop(fs, g):
+    for f in fs:
+        try:
+            try:
+                f()
+            except ValueError:
+                raise
+        except KeyError as k:
+            g(k)
+        finally:
+            g()
@@ -0,0 +1,7 @@
+from os.path import *
+from . import sibling
+
+
+def lazy():
+    from .. import helper
+    return helper
@@ -0,0 +1,9 @@
+def either(a, b):
+    x = a or b
+    return x
+
+
+def last_true(args, default):
+    for a in args:
+        default = a or default
+    return default
@@ -0,0 +1,18 @@
+def point(x, y):
+    return {"x": x, "y": y}
+
+
+def keyed(*, k=1, j=2):
+    return k + j
+
+
+def checked(x):
+    assert x, "x must be set"
+    return x
+
+
+def grouped(f):
+    try:
+        f()
+    except* ValueError:
+        pass
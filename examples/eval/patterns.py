@@ -0,0 +1,7 @@
+def locate(point):
+    match point:
+        case Point(x=0, y=yy):
+            return yy
+        case Point(x=xx, y=0):
+            return xx
+    return None
@@ -0,0 +1,13 @@
+def write(path, data):
+    with open(path, "w") as f:
+        f.write(data)
+
+
+def locked(lock, f):
+    with lock:
+        f()
+
+
+async def fetch(session):
+    async with session as s:
+        await s.get()
@@ -1,15 +1,26 @@
 //! Do abstract interpretation on the [`stack_ir`] to turn it into a simple
-//! block structure. This makes an assumption that the program does not place
-//! things on the stack except for in the context of a statement, i.e. that the
-//! stack is always empty at the start and end of every block
+//! block structure. Blocks are evaluated starting from the entry, with the
+//! stack at the end of a block carried into the blocks after it. Every path
+//! into a block has to leave a stack of the same depth, and the slots that
+//! paths leave different things in, like the value of `a or b`, become an
+//! [`Expr::Join { block, slot }`](Expr::Join). A block is evaluated again
+//! whenever its entry stack changes, until it doesn't
+//!
+//! Blocks covered by the exception table also lead to their handler, which is
+//! entered with the stack the block was entered with popped down to the
+//! handler's depth and the caught exception pushed
 
 use crate::{
     abstract_interpretation::ControlFlow,
-    objects::{CodeObject, PyObjectRegion},
-    stack_ir::{self, Instruction, JumpClass},
+    objects::{CodeObject, PyObject, PyObjectIndex, PyObjectRegion},
+    stack_ir::{
+        self, BuildKind, Coercion, Constant, DeletePlace, ExceptionHandler, FunctionAttribute,
+        Instruction, Intrinsic1, JumpClass, ParsedCode, SpecialMethod,
+    },
+    version::PythonVersion,
 };
 
-use super::{Block, Expr, Statement};
+use super::{Block, DictItem, Expr, Keyword, Place, Statement};
 use std::{
     collections::{BTreeSet, HashMap},
     ops::Range,
@@ -17,8 +28,9 @@ use std::{
 
 pub(crate) struct EvalCtx<'a> {
     code: Box<[Instruction]>,
+    exception_handlers: Box<[ExceptionHandler]>,
     code_obj: &'a CodeObject<'a>,
-    pub(crate) region: &'a PyObjectRegion,
+    pub(crate) region: &'a PyObjectRegion<'a>,
 
     stack: Vec<Expr>,
     out_blocks: HashMap<u32, Block>,
@@ -26,13 +38,14 @@ pub(crate) struct EvalCtx<'a> {
 
 impl<'a> EvalCtx<'a> {
     fn new(
-        code: Box<[Instruction]>,
+        code: ParsedCode,
         max_stack: usize,
         code_obj: &'a CodeObject<'a>,
-        region: &'a PyObjectRegion,
+        region: &'a PyObjectRegion<'a>,
     ) -> EvalCtx<'a> {
         EvalCtx {
-            code,
+            code: code.instructions.into_boxed_slice(),
+            exception_handlers: code.exception_handlers.into_boxed_slice(),
             code_obj,
             region,
             stack: Vec::with_capacity(max_stack),
@@ -41,64 +54,261 @@ impl<'a> EvalCtx<'a> {
     }
 
     fn go(&mut self) -> Result<(), EvaluationError> {
-        for block in self.blocks() {
-            println!(
-                "{:#?}",
-                &self.code[block.start as usize..block.end as usize]
-            );
-        }
-        for block in self.blocks() {
-            let start = block.start;
-            let block = self.process_block(block)?;
+        let blocks = self.blocks();
+        let mut entry_stacks = HashMap::<u32, Vec<Expr>>::new();
+        let mut worklist = vec![(0, Vec::new())];
+        while let Some((start, stack)) = worklist.pop() {
+            // Blocks are evaluated again whenever a path into them leaves
+            // something different on the stack, until nothing changes. Slots
+            // only ever change into a Join, so this always stops
+            let stack = match entry_stacks.get(&start) {
+                Some(entry_stack) => {
+                    let Some(joined) = join(start, entry_stack, stack) else {
+                        return Err(EvaluationError::InconsistentStack(start));
+                    };
+                    if joined == *entry_stack {
+                        continue;
+                    }
+                    joined
+                }
+                None => stack,
+            };
+            entry_stacks.insert(start, stack.clone());
+            let Ok(idx) = blocks.binary_search_by_key(&start, |block| block.start) else {
+                unreachable!("Every jump target starts a block")
+            };
+            self.stack = stack;
+            let (block, handler_stack) = self.process_block(blocks[idx].clone())?;
+            if let (Some(handler), Some(handler_stack)) = (block.handler, handler_stack) {
+                worklist.push((handler, handler_stack));
+            }
+            match &block.control_flow {
+                ControlFlow::Unconditional(target) => {
+                    worklist.push((*target, self.stack.clone()));
+                }
+                ControlFlow::CondtionalJump {
+                    if_true, if_false, ..
+                } => {
+                    worklist.push((*if_false, self.stack.clone()));
+                    worklist.push((*if_true, self.stack.clone()));
+                }
+                ControlFlow::ForEach { id, body, exit, .. } => {
+                    worklist.push((*exit, self.stack.clone()));
+                    let mut body_stack = self.stack.clone();
+                    body_stack.push(Expr::ForItem { id: *id });
+                    worklist.push((*body, body_stack));
+                }
+                ControlFlow::Raise { .. } | ControlFlow::Reraise(_) | ControlFlow::Terminates => {}
+            }
             self.out_blocks.insert(start, block);
         }
 
         Ok(())
     }
 
-    fn process_block(&mut self, bounds: Range<u32>) -> Result<Block, EvaluationError> {
+    /// Evaluate a block, along with the stack the handler of the block is
+    /// entered with if it has one
+    fn process_block(
+        &mut self,
+        bounds: Range<u32>,
+    ) -> Result<(Block, Option<Vec<Expr>>), EvaluationError> {
         let code_bounds = bounds.start as usize..bounds.end as usize;
         // It is actually possible to take this as owned since each block is
         // guaranteed to not overlap, but it's not super important
         let code = &self.code[code_bounds];
-        self.stack.clear();
+        let handler = self.handler_at(bounds.start).copied();
 
         let mut statements = Vec::new();
+        let mut terminator = None;
+        // Handlers are entered with the stack as it is on entry to the block,
+        // truncated to the depth. Later instructions in the block can move
+        // things around below it, like the SWAP before the POP_EXCEPT of a
+        // return from an except
+        let mut handler_stack = match &handler {
+            Some(handler) if code.first() != Some(&Instruction::PushExcInfo) => {
+                Some(handler_entry(&self.stack, handler)?)
+            }
+            _ => None,
+        };
+        if let Some((body, handler)) = self.try_at(bounds.start) {
+            statements.push(Statement::Try { body, handler });
+        }
 
-        for instruction in code {
+        for (idx, instruction) in (bounds.start..).zip(code) {
             match instruction {
                 Instruction::LoadConst(constant) => self.stack.push(Expr::Constant(*constant)),
                 Instruction::Load { from } => self.stack.push(Expr::Load {
                     from: self.code_obj.eval_place(from, self),
                 }),
-                Instruction::Store { into } => match self.stack.pop() {
-                    Some(expr) => statements.push(Statement::Store {
+                Instruction::Store { into } => {
+                    let expr = pop(&mut self.stack)?;
+                    let into = self.code_obj.eval_place(into, self);
+                    let with_target = entered(&expr)
+                        .and_then(|id| with_target(&mut statements, &mut self.out_blocks, id));
+                    match with_target {
+                        Some(target) => *target = Some(into),
+                        None => statements.push(Statement::Store { expr, into }),
+                    }
+                }
+                Instruction::Delete { place } => {
+                    let statement = match place {
+                        DeletePlace::Variable(place) => Statement::Delete {
+                            place: self.code_obj.eval_place(place, self),
+                        },
+                        DeletePlace::Attribute(name) => Statement::DeleteAttr {
+                            obj: pop(&mut self.stack)?,
+                            name: *name,
+                        },
+                        DeletePlace::Subscript => {
+                            let key = pop(&mut self.stack)?;
+                            let container = pop(&mut self.stack)?;
+                            Statement::DeleteSubscript { container, key }
+                        }
+                    };
+                    statements.push(statement);
+                }
+                // The namespace is always the one of the class body
+                Instruction::LoadFromDictOr { from } => {
+                    pop(&mut self.stack)?;
+                    self.stack.push(Expr::Load {
+                        from: self.code_obj.eval_place(from, self),
+                    });
+                }
+                Instruction::LoadLocals => self.stack.push(Expr::Locals),
+                Instruction::LoadBuildClass => self.stack.push(Expr::BuildClass),
+                Instruction::ImportName { name } => {
+                    let fromlist = pop(&mut self.stack)?;
+                    let level = pop(&mut self.stack)?;
+                    self.stack.push(Expr::Import {
+                        module: *name,
+                        fromlist: Box::new(fromlist),
+                        level: Box::new(level),
+                    });
+                }
+                Instruction::ImportFrom { name } => {
+                    let Some(module) = self.stack.last() else {
+                        return Err(EvaluationError::PoppedEmptyStack);
+                    };
+                    self.stack.push(Expr::ImportFrom {
+                        module: Box::new(module.clone()),
+                        name: *name,
+                    });
+                }
+                Instruction::SetupAnnotations => statements.push(Statement::SetupAnnotations),
+                Instruction::LoadAttr { name, method } => {
+                    let obj = pop(&mut self.stack)?;
+                    push_attribute(&mut self.stack, obj, *name, *method);
+                }
+                Instruction::StoreAttr { name } => {
+                    let obj = pop(&mut self.stack)?;
+                    let expr = pop(&mut self.stack)?;
+                    statements.push(Statement::StoreAttr {
                         expr,
-                        into: self.code_obj.eval_place(into, self),
-                    }),
-                    None => return Err(EvaluationError::PoppedEmptyStack),
-                },
-                Instruction::Pop => match self.stack.pop() {
-                    Some(expr) => statements.push(Statement::Trivial(expr)),
-                    None => return Err(EvaluationError::PoppedEmptyStack),
-                },
-                Instruction::Copy(n) => match self.stack.get(self.stack.len() - 1 - *n as usize) {
-                    Some(val) => self.stack.push(val.clone()),
-                    None => return Err(EvaluationError::StackOpOutOfBounds),
-                },
-                Instruction::Swap(n) => {
-                    if *n == 0 {
-                        continue;
+                        obj,
+                        name: *name,
+                    });
+                }
+                Instruction::LoadSuperAttr { name, method } => {
+                    let self_ = pop(&mut self.stack)?;
+                    let class = pop(&mut self.stack)?;
+                    let super_ = pop(&mut self.stack)?;
+                    let obj = Expr::Call {
+                        func: Box::new(super_),
+                        args: Box::new([class, self_]),
+                        keywords: Box::new([]),
+                    };
+                    push_attribute(&mut self.stack, obj, *name, *method);
+                }
+                Instruction::StoreSubscript => {
+                    let key = pop(&mut self.stack)?;
+                    let container = pop(&mut self.stack)?;
+                    let expr = pop(&mut self.stack)?;
+                    statements.push(Statement::StoreSubscript {
+                        expr,
+                        container,
+                        key,
+                    });
+                }
+                Instruction::Build { kind, count } => {
+                    let expr = build(&mut self.stack, *kind, *count)?;
+                    self.stack.push(expr);
+                }
+                Instruction::BuildConstKeyMap { count } => {
+                    let keys = pop(&mut self.stack)?;
+                    let values = pop_n(&mut self.stack, *count as usize)?;
+                    let Some(keys) = self.const_keys(&keys, *count) else {
+                        return Err(EvaluationError::InvalidConstKeys(keys));
+                    };
+                    let items = keys
+                        .into_iter()
+                        .zip(values)
+                        .map(|(key, value)| DictItem::Pair { key, value })
+                        .collect();
+                    self.stack.push(Expr::Dict(items));
+                }
+                Instruction::ContainerAdd { kind, depth } => {
+                    statements.extend(add_to_container(&mut self.stack, *kind, *depth, false)?)
+                }
+                Instruction::ContainerExtend { kind, depth } => {
+                    statements.extend(add_to_container(&mut self.stack, *kind, *depth, true)?)
+                }
+                Instruction::Unpack { count, star } => {
+                    let expr = pop(&mut self.stack)?;
+                    statements.push(Statement::Unpack {
+                        expr,
+                        id: idx,
+                        count: *count,
+                        star: *star,
+                    });
+                    for index in (0..*count).rev() {
+                        self.stack.push(Expr::UnpackedItem { id: idx, index });
+                    }
+                }
+                Instruction::Pop => {
+                    let expr = pop(&mut self.stack)?;
+                    // Entering without a target and exiting at the end of
+                    // the body are part of the With statement, `except`
+                    // without `as` drops the exception, and NULLs aren't values
+                    let implicit = entered(&expr).is_some()
+                        || is_exit_call(&expr)
+                        || matches!(
+                            expr,
+                            Expr::CaughtException { .. } | Expr::Constant(Constant::Null)
+                        );
+                    if !implicit {
+                        statements.push(Statement::Trivial(expr));
                     }
+                }
+                Instruction::Copy(n) => {
                     let len = self.stack.len();
-                    match self
-                        .stack
-                        .get_disjoint_mut([len - 1 - (*n as usize), len - 1])
-                    {
-                        Ok([a, b]) => std::mem::swap(a, b),
-                        _ => continue,
+                    match len.checked_sub(*n as usize) {
+                        Some(i) if i < len => self.stack.push(self.stack[i].clone()),
+                        _ => return Err(EvaluationError::StackOpOutOfBounds),
+                    }
+                }
+                Instruction::Swap(n) => {
+                    let len = self.stack.len();
+                    match len.checked_sub(*n as usize) {
+                        Some(i) if i < len => self.stack.swap(i, len - 1),
+                        _ => return Err(EvaluationError::StackOpOutOfBounds),
                     }
                 }
+                // The iterator is left on the stack, and is the same through
+                // every iteration, so dropping it isn't a statement
+                Instruction::PopIter => {
+                    pop(&mut self.stack)?;
+                }
+                Instruction::ForIter { exit_target } => {
+                    let Some(iter) = self.stack.last() else {
+                        return Err(EvaluationError::PoppedEmptyStack);
+                    };
+                    terminator = Some(ControlFlow::ForEach {
+                        iter: iter.clone(),
+                        id: idx,
+                        body: idx + 1,
+                        exit: *exit_target,
+                    });
+                }
                 Instruction::BinaryOp(op) => match (self.stack.pop(), self.stack.pop()) {
                     (Some(rhs), Some(lhs)) => self.stack.push(Expr::BinaryOp {
                         op: *op,
@@ -125,51 +335,360 @@ impl<'a> EvalCtx<'a> {
                     class: JumpClass::Always,
                     target,
                 } => statements.push(Statement::Jump { target: *target }),
-                Instruction::Call(n) => {
-                    let mut args = Vec::with_capacity(*n as usize);
-                    for _ in 0..*n {
-                        match self.stack.pop() {
-                            Some(expr) => args.push(expr),
-                            None => return Err(EvaluationError::PoppedEmptyStack),
+                Instruction::Call {
+                    count,
+                    receiver,
+                    keywords,
+                } => {
+                    let names = match keywords {
+                        true => {
+                            let names = pop(&mut self.stack)?;
+                            self.keyword_names(names)?
+                        }
+                        false => Vec::new(),
+                    };
+                    let mut args = pop_n(&mut self.stack, *count as usize)?;
+                    let Some(first_keyword) = args.len().checked_sub(names.len()) else {
+                        return Err(EvaluationError::TooManyKeywords);
+                    };
+                    let keywords = names
+                        .into_iter()
+                        .zip(args.split_off(first_keyword))
+                        .map(|(name, value)| Keyword::Named { name, value })
+                        .collect();
+                    let (func, self_) = pop_callable(&mut self.stack, *receiver)?;
+                    let expr = match func {
+                        Expr::SpecialMethod {
+                            obj,
+                            method: method @ (SpecialMethod::Enter | SpecialMethod::AsyncEnter),
+                        } => {
+                            let Some(body) = self.with_body(idx) else {
+                                return Err(EvaluationError::MissingWithCleanup(idx));
+                            };
+                            statements.push(Statement::With {
+                                context: *obj,
+                                id: idx,
+                                target: None,
+                                is_async: method == SpecialMethod::AsyncEnter,
+                                body,
+                            });
+                            Expr::WithEnter { id: idx }
+                        }
+                        Expr::BuildClass => {
+                            statements.push(self.class_def(idx, args, keywords)?);
+                            Expr::Class { id: idx }
+                        }
+                        func => {
+                            if let Some(self_) = self_ {
+                                args.insert(0, self_);
+                            }
+                            Expr::Call {
+                                func: Box::new(func),
+                                args: args.into_boxed_slice(),
+                                keywords,
+                            }
                         }
+                    };
+                    self.stack.push(expr);
+                }
+                Instruction::CallEx { receiver, kwargs } => {
+                    let kwargs = match kwargs {
+                        true => Some(pop(&mut self.stack)?),
+                        false => None,
+                    };
+                    let keywords = match kwargs {
+                        None | Some(Expr::Constant(Constant::Null)) => Box::new([]) as Box<[_]>,
+                        Some(Expr::Dict(items)) => match self.dict_keywords(&items) {
+                            Some(keywords) => keywords,
+                            None => Box::new([Keyword::DoubleStarred(Expr::Dict(items))]),
+                        },
+                        Some(kwargs) => Box::new([Keyword::DoubleStarred(kwargs)]),
+                    };
+                    let mut args = match pop(&mut self.stack)? {
+                        Expr::Tuple(items) => items.into_vec(),
+                        args => vec![Expr::Starred(Box::new(args))],
+                    };
+                    let (func, self_) = pop_callable(&mut self.stack, *receiver)?;
+                    if let Some(self_) = self_ {
+                        args.insert(0, self_);
                     }
-                    let receiver = match self.stack.pop() {
-                        Some(expr) => expr,
-                        None => return Err(EvaluationError::PoppedEmptyStack),
+                    let expr = match func {
+                        Expr::BuildClass => {
+                            statements.push(self.class_def(idx, args, keywords)?);
+                            Expr::Class { id: idx }
+                        }
+                        func => Expr::Call {
+                            func: Box::new(func),
+                            args: args.into_boxed_slice(),
+                            keywords,
+                        },
+                    };
+                    self.stack.push(expr);
+                }
+                Instruction::CallIntrinsic1(intrinsic) => {
+                    let expr = match (intrinsic, pop(&mut self.stack)?) {
+                        (Intrinsic1::ListToTuple, Expr::List(items)) => Expr::Tuple(items),
+                        (intrinsic, expr) => Expr::CallIntrinsic1(*intrinsic, Box::new(expr)),
+                    };
+                    self.stack.push(expr);
+                }
+                Instruction::CallIntrinsic2(intrinsic) => {
+                    let rhs = pop(&mut self.stack)?;
+                    let lhs = pop(&mut self.stack)?;
+                    self.stack.push(Expr::CallIntrinsic2(
+                        *intrinsic,
+                        Box::new(lhs),
+                        Box::new(rhs),
+                    ));
+                }
+                Instruction::Yield => {
+                    let expr = match pop(&mut self.stack)? {
+                        // Async generators wrap what they yield
+                        Expr::CallIntrinsic1(Intrinsic1::AsyncGenWrap, expr) => expr,
+                        expr => Box::new(expr),
                     };
-                    let func = match self.stack.pop() {
-                        Some(expr) => expr,
-                        None => return Err(EvaluationError::PoppedEmptyStack),
+                    self.stack.push(Expr::Yield(expr));
+                }
+                Instruction::YieldFrom => {
+                    // The first value sent is always None
+                    pop(&mut self.stack)?;
+                    let Some(receiver) = self.stack.last() else {
+                        return Err(EvaluationError::PoppedEmptyStack);
                     };
-                    self.stack.push(Expr::Call {
-                        func: Box::new(func),
-                        receiver: Box::new(receiver),
-                        args: args.into_boxed_slice(),
+                    self.stack.push(delegate(receiver.clone()));
+                }
+                Instruction::CleanupThrow => {
+                    pop(&mut self.stack)?;
+                    pop(&mut self.stack)?;
+                    let Some(receiver) = self.stack.last() else {
+                        return Err(EvaluationError::PoppedEmptyStack);
+                    };
+                    self.stack.push(delegate(receiver.clone()));
+                }
+                Instruction::EndSend => {
+                    let value = pop(&mut self.stack)?;
+                    pop(&mut self.stack)?;
+                    self.stack.push(value);
+                }
+                Instruction::Reraise => {
+                    let exc = pop(&mut self.stack)?;
+                    terminator = Some(ControlFlow::Reraise(exc));
+                }
+                Instruction::Raise { count } => {
+                    let (exception, cause) = match count {
+                        0 => (None, None),
+                        1 => (Some(pop(&mut self.stack)?), None),
+                        2 => {
+                            let cause = pop(&mut self.stack)?;
+                            (Some(pop(&mut self.stack)?), Some(cause))
+                        }
+                        n => return Err(EvaluationError::InvalidRaiseCount(*n)),
+                    };
+                    terminator = Some(ControlFlow::Raise { exception, cause });
+                }
+                Instruction::CheckExcMatch => {
+                    let class = pop(&mut self.stack)?;
+                    let Some(exception) = self.stack.last() else {
+                        return Err(EvaluationError::PoppedEmptyStack);
+                    };
+                    self.stack.push(Expr::ExceptionMatches {
+                        exception: Box::new(exception.clone()),
+                        class: Box::new(class),
+                    });
+                }
+                Instruction::CheckEgMatch => {
+                    let class = Box::new(pop(&mut self.stack)?);
+                    let exception = Box::new(pop(&mut self.stack)?);
+                    self.stack.push(Expr::ExceptionGroupRest {
+                        exception: exception.clone(),
+                        class: class.clone(),
                     });
+                    self.stack
+                        .push(Expr::ExceptionGroupMatch { exception, class });
+                }
+                Instruction::PushExcInfo => {
+                    let exc = pop(&mut self.stack)?;
+                    self.stack.push(Expr::PreviousException);
+                    self.stack.push(exc);
+                }
+                Instruction::PopExcept => {
+                    pop(&mut self.stack)?;
+                }
+                Instruction::LoadSpecial(method) => {
+                    let obj = pop(&mut self.stack)?;
+                    self.stack.push(Expr::SpecialMethod {
+                        obj: Box::new(obj),
+                        method: *method,
+                    });
+                    self.stack.push(Expr::Constant(Constant::Null));
+                }
+                // Self is always NULL, since special methods are represented
+                // like method loads
+                Instruction::WithExceptStart { exit_depth } => {
+                    let len = self.stack.len();
+                    let (Some(exit), Some(exception)) = (
+                        len.checked_sub(*exit_depth as usize)
+                            .map(|i| &self.stack[i]),
+                        self.stack.last(),
+                    ) else {
+                        return Err(EvaluationError::StackOpOutOfBounds);
+                    };
+                    let expr = Expr::WithExit {
+                        exit: Box::new(exit.clone()),
+                        exception: Box::new(exception.clone()),
+                    };
+                    self.stack.push(expr);
                 }
                 Instruction::Return => match self.stack.pop() {
                     Some(expr) => statements.push(Statement::Return(expr)),
                     None => return Err(EvaluationError::PoppedEmptyStack),
                 },
-                Instruction::MakeFunction => match self.stack.pop() {
-                    Some(expr) => self.stack.push(Expr::MakeFunction(Box::new(expr))),
-                    None => return Err(EvaluationError::PoppedEmptyStack),
-                },
+                Instruction::MakeFunction => {
+                    let code = pop(&mut self.stack)?;
+                    let Some(code) = self.const_code(&code) else {
+                        return Err(EvaluationError::NotACodeObject(code));
+                    };
+                    self.stack.push(Expr::MakeFunction {
+                        code,
+                        defaults: None,
+                        kwdefaults: None,
+                        annotations: None,
+                        annotate: None,
+                        closure: None,
+                    });
+                }
+                Instruction::SetFunctionAttribute(attribute) => {
+                    let mut func = pop(&mut self.stack)?;
+                    let value = Box::new(pop(&mut self.stack)?);
+                    if let Expr::MakeFunction {
+                        defaults,
+                        kwdefaults,
+                        annotations,
+                        annotate,
+                        closure,
+                        ..
+                    } = &mut func
+                    {
+                        let field = match attribute {
+                            FunctionAttribute::Defaults => defaults,
+                            FunctionAttribute::KwDefaults => kwdefaults,
+                            FunctionAttribute::Annotations => annotations,
+                            FunctionAttribute::Annotate => annotate,
+                            FunctionAttribute::Closure => closure,
+                        };
+                        *field = Some(value);
+                    } else {
+                        return Err(EvaluationError::NotAFunction(func));
+                    }
+                    self.stack.push(func);
+                }
                 Instruction::Coercion(coercion) => match self.stack.pop() {
                     Some(expr) => self.stack.push(Expr::Coercion(*coercion, Box::new(expr))),
                     None => return Err(EvaluationError::PoppedEmptyStack),
                 },
+                Instruction::MatchClass { count } => {
+                    let keywords = Box::new(pop(&mut self.stack)?);
+                    let class = Box::new(pop(&mut self.stack)?);
+                    let subject = Box::new(pop(&mut self.stack)?);
+                    self.stack.push(Expr::MatchClass {
+                        subject,
+                        class,
+                        count: *count,
+                        keywords,
+                    });
+                }
+                Instruction::MatchMapping => {
+                    let subject = peek(&self.stack)?;
+                    self.stack.push(Expr::MatchMapping(subject));
+                }
+                Instruction::MatchSequence => {
+                    let subject = peek(&self.stack)?;
+                    self.stack.push(Expr::MatchSequence(subject));
+                }
+                Instruction::MatchKeys => {
+                    let len = self.stack.len();
+                    let Some(subject) = len.checked_sub(2).map(|i| &self.stack[i]) else {
+                        return Err(EvaluationError::StackOpOutOfBounds);
+                    };
+                    let expr = Expr::MatchKeys {
+                        subject: Box::new(subject.clone()),
+                        keys: peek(&self.stack)?,
+                    };
+                    self.stack.push(expr);
+                }
+                Instruction::GetLen => {
+                    let expr = peek(&self.stack)?;
+                    self.stack.push(Expr::Len(expr));
+                }
+                Instruction::ConvertValue(conversion) => {
+                    let value = Box::new(pop(&mut self.stack)?);
+                    self.stack.push(Expr::FormattedValue {
+                        value,
+                        conversion: Some(*conversion),
+                        spec: None,
+                    });
+                }
+                Instruction::FormatValue { spec } => {
+                    let spec = match spec {
+                        true => Some(Box::new(pop(&mut self.stack)?)),
+                        false => None,
+                    };
+                    let expr = match pop(&mut self.stack)? {
+                        // A converted value is always formatted straight after
+                        Expr::FormattedValue {
+                            value,
+                            conversion: conversion @ Some(_),
+                            spec: None,
+                        } => Expr::FormattedValue {
+                            value,
+                            conversion,
+                            spec,
+                        },
+                        value => Expr::FormattedValue {
+                            value: Box::new(value),
+                            conversion: None,
+                            spec,
+                        },
+                    };
+                    self.stack.push(expr);
+                }
+                Instruction::BuildInterpolation { conversion, spec } => {
+                    let spec = match spec {
+                        true => Some(Box::new(pop(&mut self.stack)?)),
+                        false => None,
+                    };
+                    let expression = Box::new(pop(&mut self.stack)?);
+                    let value = Box::new(pop(&mut self.stack)?);
+                    self.stack.push(Expr::Interpolation {
+                        value,
+                        expression,
+                        conversion: *conversion,
+                        spec,
+                    });
+                }
+                Instruction::BuildTemplate => {
+                    let interpolations = Box::new(pop(&mut self.stack)?);
+                    let strings = Box::new(pop(&mut self.stack)?);
+                    self.stack.push(Expr::Template {
+                        strings,
+                        interpolations,
+                    });
+                }
             }
-        }
 
-        if !self.stack.is_empty() {
-            return Err(EvaluationError::BlockWithNonEmptyStack(
-                self.stack.clone(),
-                code.iter().map(Clone::clone).collect(),
-            ));
+            // A handler that is itself protected pushes the previous exception
+            // its handler expects below the depth, and PUSH_EXC_INFO can't raise
+            if idx == bounds.start
+                && let Instruction::PushExcInfo = instruction
+                && let Some(handler) = &handler
+            {
+                handler_stack = Some(handler_entry(&self.stack, handler)?);
+            }
         }
 
         let control_flow = match statements.last() {
+            // These always end a block
+            _ if let Some(terminator) = terminator => terminator,
             Some(Statement::Return(_)) => ControlFlow::Terminates,
             Some(Statement::If { expr: _, target: _ }) => {
                 let Some(Statement::If {
@@ -180,8 +699,8 @@ impl<'a> EvalCtx<'a> {
                     unreachable!()
                 };
                 ControlFlow::CondtionalJump {
-                    if_true: (&bounds).end as u32,
-                    if_false: if_false,
+                    if_true: bounds.end,
+                    if_false,
                     expr,
                 }
             }
@@ -195,12 +714,174 @@ impl<'a> EvalCtx<'a> {
             _ => ControlFlow::Unconditional(bounds.end),
         };
 
-        Ok(Block {
+        let block = Block {
             body: statements.into_boxed_slice(),
             control_flow,
+            handler: handler.map(|handler| handler.target),
+        };
+        Ok((block, handler_stack))
+    }
+
+    fn constant(&self, index: u32) -> Option<&PyObject<'a>> {
+        self.region.get(self.code_obj.constant(index, self.region)?)
+    }
+
+    /// The index in the region of the code object that is loaded
+    fn const_code(&self, expr: &Expr) -> Option<PyObjectIndex> {
+        let Expr::Constant(Constant::ByIndex(i)) = expr else {
+            return None;
+        };
+        let index = self.code_obj.constant(*i, self.region)?;
+        matches!(self.region.get(index), Some(PyObject::Code(_))).then_some(index)
+    }
+
+    /// The class statement of a call to `__build_class__` with these arguments,
+    /// which are the function of the class body, the name and the bases
+    fn class_def(
+        &self,
+        idx: u32,
+        args: Vec<Expr>,
+        keywords: Box<[Keyword]>,
+    ) -> Result<Statement, EvaluationError> {
+        let (body_code, name) = match &args[..] {
+            [Expr::MakeFunction { code, .. }, name, ..] => (Some(*code), self.const_string(name)),
+            _ => (None, None),
+        };
+        let (Some(body_code), Some(name)) = (body_code, name) else {
+            return Err(EvaluationError::InvalidClassDef(Expr::Call {
+                func: Box::new(Expr::BuildClass),
+                args: args.into_boxed_slice(),
+                keywords,
+            }));
+        };
+        Ok(Statement::ClassDef {
+            name,
+            bases: args.into_iter().skip(2).collect(),
+            keywords,
+            body_code,
+            id: idx,
         })
     }
 
+    fn const_string(&self, expr: &Expr) -> Option<String> {
+        let object = match expr {
+            Expr::Constant(Constant::ByIndex(i)) => self.constant(*i),
+            Expr::Object(index) => self.region.get(*index),
+            _ => None,
+        };
+        match object {
+            Some(PyObject::String(s)) => Some(s.to_string()),
+            _ => None,
+        }
+    }
+
+    /// The items of the constant tuple of keys of `BUILD_CONST_KEY_MAP`, if
+    /// there are count of them
+    fn const_keys(&self, keys: &Expr, count: u32) -> Option<Vec<Expr>> {
+        let Expr::Constant(Constant::ByIndex(i)) = keys else {
+            return None;
+        };
+        match self.constant(*i) {
+            Some(PyObject::Tuple(items)) if items.len() == count as usize => {
+                Some(items.iter().copied().map(Expr::Object).collect())
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolve the tuple of keyword names pushed for a call with keywords
+    fn keyword_names(&self, names: Expr) -> Result<Vec<String>, EvaluationError> {
+        let resolved = match &names {
+            Expr::Constant(Constant::ByIndex(i)) => match self.constant(*i) {
+                Some(PyObject::Tuple(items)) => items
+                    .iter()
+                    .map(|item| match self.region.get(*item) {
+                        Some(PyObject::String(s)) => Some(s.to_string()),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => None,
+            },
+            _ => None,
+        };
+        resolved.ok_or(EvaluationError::InvalidKeywordNames(names))
+    }
+
+    /// Turn the items of a dict built for `CALL_FUNCTION_EX` into keywords, if
+    /// all the keys are constant strings
+    fn dict_keywords(&self, items: &[DictItem]) -> Option<Box<[Keyword]>> {
+        items
+            .iter()
+            .map(|item| match item {
+                DictItem::Pair { key, value } => Some(Keyword::Named {
+                    name: self.const_string(key)?,
+                    value: value.clone(),
+                }),
+                DictItem::DoubleStarred(expr) => Some(Keyword::DoubleStarred(expr.clone())),
+            })
+            .collect()
+    }
+
+    /// The instructions in the with statement entered at idx, which are the
+    /// ones protected by the first with cleanup handler after it
+    fn with_body(&self, idx: u32) -> Option<Range<u32>> {
+        let is_cleanup = |handler: &&ExceptionHandler| {
+            let target = handler.target as usize;
+            matches!(
+                self.code.get(target..target + 2),
+                Some([
+                    Instruction::PushExcInfo,
+                    Instruction::WithExceptStart { .. }
+                ])
+            )
+        };
+        let first = self
+            .exception_handlers
+            .iter()
+            .filter(|handler| handler.start > idx)
+            .filter(is_cleanup)
+            .min_by_key(|handler| handler.start)?;
+        Some(self.handled_range(first.target))
+    }
+
+    /// The try statement whose body starts here, if this is the start of the
+    /// first range of a handler that isn't a with cleanup or the cleanup of
+    /// another handler, which don't start with PUSH_EXC_INFO
+    fn try_at(&self, start: u32) -> Option<(Range<u32>, u32)> {
+        let handler = self
+            .exception_handlers
+            .iter()
+            .find(|handler| handler.start == start)?;
+        let target = handler.target as usize;
+        let is_try = matches!(
+            self.code.get(target..target + 2),
+            Some([Instruction::PushExcInfo, next])
+                if !matches!(next, Instruction::WithExceptStart { .. })
+        );
+        let body = self.handled_range(handler.target);
+        (is_try && body.start == start).then_some((body, handler.target))
+    }
+
+    /// The instructions from the start of the first range that jumps to the
+    /// handler to the end of the last one. Nested blocks split the range into
+    /// several entries
+    fn handled_range(&self, target: u32) -> Range<u32> {
+        let entries = self
+            .exception_handlers
+            .iter()
+            .filter(|handler| handler.target == target);
+        let start = entries.clone().map(|handler| handler.start).min();
+        let end = entries.map(|handler| handler.end).max();
+        start.unwrap_or(0)..end.unwrap_or(0)
+    }
+
+    /// The exception table entry that covers this instruction
+    fn handler_at(&self, idx: u32) -> Option<&ExceptionHandler> {
+        self.exception_handlers
+            .iter()
+            .find(|handler| (handler.start..handler.end).contains(&idx))
+    }
+
     fn blocks(&self) -> Vec<Range<u32>> {
         let mut boundaries = BTreeSet::new();
         boundaries.insert(0);
@@ -221,12 +902,20 @@ impl<'a> EvalCtx<'a> {
                     boundaries.insert(*target);
                     boundaries.insert((idx as u32) + 1);
                 }
-                Instruction::Return => {
+                Instruction::ForIter { exit_target } => {
+                    boundaries.insert(*exit_target);
+                    boundaries.insert((idx as u32) + 1);
+                }
+                Instruction::Return | Instruction::Reraise | Instruction::Raise { .. } => {
                     boundaries.insert((idx as u32) + 1);
                 }
                 _ => continue,
             }
         }
+        // Blocks have at most one handler, for every instruction in them
+        for handler in self.exception_handlers.iter() {
+            boundaries.extend([handler.start, handler.end, handler.target]);
+        }
 
         let mut out = Vec::with_capacity(boundaries.len() + 1);
         boundaries.into_iter().reduce(|prev, next| {
@@ -237,12 +926,272 @@ impl<'a> EvalCtx<'a> {
     }
 }
 
+/// The stack a block is entered with when a path into it leaves this stack,
+/// after it was already entered with entry_stack. The slots that are
+/// different become a [`Expr::Join`], and there is no stack that works for
+/// both if they have different depths
+fn join(block: u32, entry_stack: &[Expr], stack: Vec<Expr>) -> Option<Vec<Expr>> {
+    if entry_stack.len() != stack.len() {
+        return None;
+    }
+    let joined = (0..)
+        .zip(entry_stack.iter().zip(stack))
+        .map(|(slot, (entry, expr))| match *entry == expr {
+            true => expr,
+            false => Expr::Join { block, slot },
+        })
+        .collect();
+    Some(joined)
+}
+
+fn pop(stack: &mut Vec<Expr>) -> Result<Expr, EvaluationError> {
+    stack.pop().ok_or(EvaluationError::PoppedEmptyStack)
+}
+
+/// A copy of the top of the stack, for instructions that leave it there
+fn peek(stack: &[Expr]) -> Result<Box<Expr>, EvaluationError> {
+    match stack.last() {
+        Some(expr) => Ok(Box::new(expr.clone())),
+        None => Err(EvaluationError::PoppedEmptyStack),
+    }
+}
+
+/// The stack that the handler is entered with, when an exception is raised
+/// with this stack
+fn handler_entry(stack: &[Expr], handler: &ExceptionHandler) -> Result<Vec<Expr>, EvaluationError> {
+    let Some(below) = stack.get(..handler.depth as usize) else {
+        return Err(EvaluationError::StackOpOutOfBounds);
+    };
+    let mut stack = below.to_vec();
+    if handler.lasti {
+        stack.push(Expr::Lasti);
+    }
+    stack.push(Expr::CaughtException {
+        handler: handler.target,
+    });
+    Ok(stack)
+}
+
+/// The id of the With statement if this is the result of entering it, which
+/// is awaited for `async with`
+fn entered(expr: &Expr) -> Option<u32> {
+    match expr {
+        Expr::WithEnter { id } => Some(*id),
+        Expr::Await(expr) => match **expr {
+            Expr::WithEnter { id } => Some(id),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_exit_call(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call { func, .. } => matches!(
+            **func,
+            Expr::SpecialMethod {
+                method: SpecialMethod::Exit | SpecialMethod::AsyncExit,
+                ..
+            }
+        ),
+        Expr::Await(expr) => is_exit_call(expr),
+        _ => false,
+    }
+}
+
+/// Find the target of the With statement with this id, which is either in the
+/// current block or one that was already evaluated
+fn with_target<'s>(
+    statements: &'s mut [Statement],
+    out_blocks: &'s mut HashMap<u32, Block>,
+    id: u32,
+) -> Option<&'s mut Option<Place>> {
+    statements
+        .iter_mut()
+        .chain(
+            out_blocks
+                .values_mut()
+                .flat_map(|block| block.body.iter_mut()),
+        )
+        .find_map(|statement| match statement {
+            Statement::With {
+                id: with_id,
+                target,
+                ..
+            } if *with_id == id => Some(target),
+            _ => None,
+        })
+}
+
+/// Push the result of loading an attribute. Method loads push the method and
+/// self (or the attribute and NULL) for a call, which is represented as the
+/// attribute and a NULL receiver, since which one it is depends on the type of
+/// the object at runtime
+fn push_attribute(stack: &mut Vec<Expr>, obj: Expr, name: u32, method: bool) {
+    stack.push(Expr::Attribute {
+        obj: Box::new(obj),
+        name,
+    });
+    if method {
+        stack.push(Expr::Constant(Constant::Null));
+    }
+}
+
+/// Pop the callable of a call, and self if it has a receiver that isn't NULL.
+/// NULL is either below the callable (up to 3.12) or above it, and methods are
+/// below self in either case
+fn pop_callable(
+    stack: &mut Vec<Expr>,
+    receiver: bool,
+) -> Result<(Expr, Option<Expr>), EvaluationError> {
+    if !receiver {
+        return Ok((pop(stack)?, None));
+    }
+    let second = pop(stack)?;
+    let first = pop(stack)?;
+    Ok(match (first, second) {
+        (Expr::Constant(Constant::Null), func) | (func, Expr::Constant(Constant::Null)) => {
+            (func, None)
+        }
+        (func, self_) => (func, Some(self_)),
+    })
+}
+
+/// The result of a `yield from` or `await` of the receiver
+fn delegate(receiver: Expr) -> Expr {
+    match receiver {
+        Expr::Coercion(Coercion::Awaitable, expr) => Expr::Await(expr),
+        Expr::Coercion(Coercion::YieldFromIter, expr) => Expr::YieldFrom(expr),
+        // Awaiting `anext` of an async iterator
+        expr @ Expr::Coercion(Coercion::AsyncNext, _) => Expr::Await(Box::new(expr)),
+        expr => Expr::YieldFrom(Box::new(expr)),
+    }
+}
+
+fn pop_n(stack: &mut Vec<Expr>, n: usize) -> Result<Vec<Expr>, EvaluationError> {
+    match stack.len().checked_sub(n) {
+        Some(start) => Ok(stack.split_off(start)),
+        None => Err(EvaluationError::PoppedEmptyStack),
+    }
+}
+
+fn build(stack: &mut Vec<Expr>, kind: BuildKind, count: u32) -> Result<Expr, EvaluationError> {
+    let count = count as usize;
+    Ok(match kind {
+        BuildKind::Tuple => Expr::Tuple(pop_n(stack, count)?.into_boxed_slice()),
+        BuildKind::List => Expr::List(pop_n(stack, count)?.into_boxed_slice()),
+        BuildKind::Set => Expr::Set(pop_n(stack, count)?.into_boxed_slice()),
+        BuildKind::String => Expr::FormattedString(pop_n(stack, count)?.into_boxed_slice()),
+        BuildKind::Dict => {
+            let mut items = pop_n(stack, 2 * count)?.into_iter();
+            let mut pairs = Vec::with_capacity(count);
+            while let (Some(key), Some(value)) = (items.next(), items.next()) {
+                pairs.push(DictItem::Pair { key, value });
+            }
+            Expr::Dict(pairs.into_boxed_slice())
+        }
+        BuildKind::Slice => {
+            let step = match count {
+                2 => None,
+                3 => Some(Box::new(pop(stack)?)),
+                _ => return Err(EvaluationError::InvalidSliceCount(count as u32)),
+            };
+            let stop = Box::new(pop(stack)?);
+            let start = Box::new(pop(stack)?);
+            Expr::Slice { start, stop, step }
+        }
+    })
+}
+
+/// Add the item on top of the stack to the container at depth. Literals are
+/// rewritten to hold the item, since the container is still being built, and
+/// anything else gets a statement that adds it
+fn add_to_container(
+    stack: &mut Vec<Expr>,
+    kind: BuildKind,
+    depth: u32,
+    extend: bool,
+) -> Result<Option<Statement>, EvaluationError> {
+    fn append<T>(items: &mut Box<[T]>, item: T) {
+        let mut vec = std::mem::take(items).into_vec();
+        vec.push(item);
+        *items = vec.into_boxed_slice();
+    }
+
+    let value = pop(stack)?;
+    let key = match (kind, extend) {
+        (BuildKind::Dict, false) => Some(pop(stack)?),
+        _ => None,
+    };
+    let Some(container) = stack
+        .len()
+        .checked_sub(depth as usize)
+        .and_then(|idx| stack.get_mut(idx))
+    else {
+        return Err(EvaluationError::StackOpOutOfBounds);
+    };
+    let item = |value| match extend {
+        true => Expr::Starred(Box::new(value)),
+        false => value,
+    };
+    let dict_item = |value| match key {
+        Some(key) => DictItem::Pair { key, value },
+        None => DictItem::DoubleStarred(value),
+    };
+    match (container, kind) {
+        (Expr::List(items), BuildKind::List) | (Expr::Set(items), BuildKind::Set) => {
+            append(items, item(value));
+        }
+        (Expr::Dict(items), BuildKind::Dict) => append(items, dict_item(value)),
+        (container @ (Expr::List(_) | Expr::Set(_) | Expr::Dict(_)), _)
+        | (container, BuildKind::Tuple | BuildKind::Slice | BuildKind::String) => {
+            return Err(EvaluationError::NotAContainer(container.clone()));
+        }
+        (container, BuildKind::Dict) => {
+            return Ok(Some(Statement::DictAdd {
+                container: container.clone(),
+                item: dict_item(value),
+            }));
+        }
+        (container, kind) => {
+            return Ok(Some(Statement::ContainerAdd {
+                container: container.clone(),
+                kind,
+                item: item(value),
+            }));
+        }
+    }
+    Ok(None)
+}
+
 #[derive(Debug)]
 pub enum EvaluationError {
     ParseError(stack_ir::parse::IRParseError),
     PoppedEmptyStack,
     StackOpOutOfBounds,
-    BlockWithNonEmptyStack(Vec<Expr>, Vec<Instruction>),
+    // The block starting here can be reached with stacks of different depths
+    InconsistentStack(u32),
+    InvalidSliceCount(u32),
+    // Tried to add items to a literal of another kind, or with a kind that
+    // can't be added to
+    NotAContainer(Expr),
+    // The keyword names of a call aren't a constant tuple of strings
+    InvalidKeywordNames(Expr),
+    // The keys of a dict built from constant keys aren't a constant tuple with
+    // one for each value
+    InvalidConstKeys(Expr),
+    TooManyKeywords,
+    // The with statement entered here doesn't have a cleanup handler
+    MissingWithCleanup(u32),
+    InvalidRaiseCount(u32),
+    // Tried to set an attribute of something that isn't a function that was
+    // made in the same block
+    NotAFunction(Expr),
+    // Functions can only be made from constant code objects
+    NotACodeObject(Expr),
+    // `__build_class__` wasn't called with a function and a constant name
+    InvalidClassDef(Expr),
+    UnsupportedVersion(PythonVersion),
 }
 
 impl From<stack_ir::parse::IRParseError> for EvaluationError {
@@ -251,18 +1200,1656 @@ impl From<stack_ir::parse::IRParseError> for EvaluationError {
     }
 }
 
-pub fn eval314(
+/// Evaluate a code object of the given version. Only code objects from 3.11
+/// onwards can be unmarshalled
+pub fn eval(
+    version: PythonVersion,
     input: CodeObject,
     region: &PyObjectRegion,
 ) -> Result<HashMap<u32, Block>, EvaluationError> {
-    let instrs = stack_ir::parse::parse314(input.code(&region))?;
-    let mut ctx = EvalCtx::new(
-        instrs.into_boxed_slice(),
-        input.stack_size() as usize,
-        &input,
-        region,
-    );
+    if version < PythonVersion::V3_11 {
+        return Err(EvaluationError::UnsupportedVersion(version));
+    }
+    let parsed = stack_ir::parse(version, input.code(region), input.exception_table(region))?;
+    let mut ctx = EvalCtx::new(parsed, input.stack_size() as usize, &input, region);
     ctx.go()?;
 
     Ok(ctx.out_blocks)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        objects::{CodeObjectConstructor, PyObjectIndex},
+        opcode::{Opcode, OpcodeTable},
+        stack_ir::{CommonConstant, Intrinsic2, UnresolvedPlace},
+        unmarshal::Unmarshaller,
+    };
+    use pretty_assertions::assert_eq;
+    use std::borrow::Cow;
+
+    // `g` and `f` of eval_closure, marshalled from 3.14
+    const CLOSURE: &[u8] = b"\xe3\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x02\x00\x00\x00\x13\x00\x00\x00\xf3\x16\x00\x00\x00<\x01\x80\x00S\x01V\x00,\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00#\x00)\x01N\xa9\x00)\x02\xda\x01y\xda\x01xs\x02\x00\x00\x00&\x80\xda\x07example\xda\x01g\xda\x0cf.<locals>.g\x02\x00\x00\x00s\x0c\x00\x00\x00\xf8\x80\x00\xd8\x0f\x10\x90\x11\x8ds\x88\n\xf3\x00\x00\x00\x00";
+    const NESTED: &[u8] = b"\xe3\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x02\x00\x00\x00\x03\x00\x00\x00\xf3\x14\x00\x00\x00a\x00\x80\x00V\x003\x01R\x00\x17\x00l\x08p\x01V\x01#\x00)\x01\xe3\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x02\x00\x00\x00\x13\x00\x00\x00\xf3\x16\x00\x00\x00<\x01\x80\x00S\x01V\x00,\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00#\x00)\x01N\xa9\x00)\x02\xda\x01y\xda\x01xs\x02\x00\x00\x00&\x80\xda\x07example\xda\x01g\xda\x0cf.<locals>.g\x02\x00\x00\x00s\x0c\x00\x00\x00\xf8\x80\x00\xd8\x0f\x10\x90\x11\x8ds\x88\n\xf3\x00\x00\x00\x00r\x04\x00\x00\x00)\x02r\x06\x00\x00\x00r\x08\x00\x00\x00s\x02\x00\x00\x00f r\x07\x00\x00\x00\xda\x01fr\x0b\x00\x00\x00\x01\x00\x00\x00s\x0d\x00\x00\x00\xf8\x80\x00\xf5\x02\x01\x05\x13\xe0\x0b\x0c\x80Hr\x0a\x00\x00\x00";
+
+    /// Evaluate the function called name in a module marshalled by the given
+    /// version
+    fn eval_function(
+        version: PythonVersion,
+        module: &[u8],
+        name: &str,
+    ) -> Result<HashMap<u32, Block>, EvaluationError> {
+        let region = Unmarshaller::loads(module).unwrap();
+        let Some(code) = region.0.iter().find_map(|object| match object {
+            PyObject::Code(code)
+                if matches!(region.get(code.name), Some(PyObject::String(s)) if s == name) =>
+            {
+                Some(code)
+            }
+            _ => None,
+        }) else {
+            panic!("Expected a function called {name}")
+        };
+        eval(version, code.construct(&region).unwrap(), &region)
+    }
+
+    /// Evaluate some IR in a code object without any names or locals, with the
+    /// given objects as its constants. Constants can refer to each other by
+    /// their index in the list
+    fn eval_with_consts(
+        code: Vec<Instruction>,
+        consts: Vec<PyObject<'static>>,
+    ) -> Result<HashMap<u32, Block>, EvaluationError> {
+        let code = ParsedCode {
+            instructions: code,
+            exception_handlers: Vec::new(),
+            origins: Vec::new(),
+        };
+        eval_parsed(code, consts)
+    }
+
+    fn eval_parsed(
+        code: ParsedCode,
+        consts: Vec<PyObject<'static>>,
+    ) -> Result<HashMap<u32, Block>, EvaluationError> {
+        let n = consts.len();
+        let mut objects = consts;
+        objects.push(PyObject::Tuple((0..n).map(PyObjectIndex).collect()));
+        objects.push(PyObject::Tuple(Box::new([])));
+        objects.push(PyObject::Bytes(Cow::Borrowed(&[])));
+        let (consts, empty_tuple, empty_bytes) =
+            (PyObjectIndex(n), PyObjectIndex(n + 1), PyObjectIndex(n + 2));
+        let constructor = CodeObjectConstructor {
+            consts,
+            ..empty_code(empty_tuple, empty_bytes)
+        };
+        let region = PyObjectRegion(objects);
+        let code_obj = CodeObject(&constructor);
+        let mut ctx = EvalCtx::new(code, 0, &code_obj, &region);
+        ctx.go()?;
+        Ok(ctx.out_blocks)
+    }
+
+    /// A code object where everything is empty, for constants of code objects
+    /// that aren't evaluated
+    fn empty_code(tuple: PyObjectIndex, bytes: PyObjectIndex) -> CodeObjectConstructor {
+        CodeObjectConstructor {
+            arg_count: 0,
+            pos_only_arg_count: 0,
+            kw_only_arg_count: 0,
+            stack_size: 0,
+            flags: 0,
+            code: bytes,
+            consts: tuple,
+            names: tuple,
+            locals_plus_names: tuple,
+            locals_plus_kinds: bytes,
+            filename: bytes,
+            name: bytes,
+            qualified_name: bytes,
+            first_line_no: 0,
+            line_table: bytes,
+            exception_table: bytes,
+        }
+    }
+
+    fn eval_ir(code: Vec<Instruction>) -> Result<HashMap<u32, Block>, EvaluationError> {
+        eval_with_consts(code, Vec::new())
+    }
+
+    fn local(n: u32) -> Instruction {
+        Instruction::Load {
+            from: UnresolvedPlace::Local(n),
+        }
+    }
+
+    fn load_local(n: u32) -> Expr {
+        Expr::Load {
+            from: Place::Local(n),
+        }
+    }
+
+    #[test]
+    /// Test `[*a, b]`, `{k: a, **b}` and `a[b:c]`
+    fn eval_containers() {
+        let blocks = eval_ir(vec![
+            Instruction::Build {
+                kind: BuildKind::List,
+                count: 0,
+            },
+            local(0),
+            Instruction::ContainerExtend {
+                kind: BuildKind::List,
+                depth: 1,
+            },
+            local(1),
+            Instruction::ContainerAdd {
+                kind: BuildKind::List,
+                depth: 1,
+            },
+            Instruction::Pop,
+            Instruction::LoadConst(Constant::ByIndex(0)),
+            local(0),
+            Instruction::Build {
+                kind: BuildKind::Dict,
+                count: 1,
+            },
+            local(1),
+            Instruction::ContainerExtend {
+                kind: BuildKind::Dict,
+                depth: 1,
+            },
+            Instruction::Pop,
+            local(0),
+            local(1),
+            local(2),
+            Instruction::Build {
+                kind: BuildKind::Slice,
+                count: 2,
+            },
+            Instruction::BinaryOp(stack_ir::BinOp::Subscript),
+            Instruction::Return,
+        ])
+        .unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![
+                Statement::Trivial(Expr::List(Box::new([
+                    Expr::Starred(Box::new(load_local(0))),
+                    load_local(1)
+                ]))),
+                Statement::Trivial(Expr::Dict(Box::new([
+                    DictItem::Pair {
+                        key: Expr::Constant(Constant::ByIndex(0)),
+                        value: load_local(0)
+                    },
+                    DictItem::DoubleStarred(load_local(1))
+                ]))),
+                Statement::Return(Expr::BinaryOp {
+                    op: stack_ir::BinOp::Subscript,
+                    lhs: Box::new(load_local(0)),
+                    rhs: Box::new(Expr::Slice {
+                        start: Box::new(load_local(1)),
+                        stop: Box::new(load_local(2)),
+                        step: None
+                    })
+                })
+            ]
+            .into_boxed_slice()
+        );
+
+        let blocks = eval_ir(vec![
+            local(0),
+            local(1),
+            Instruction::ContainerAdd {
+                kind: BuildKind::Set,
+                depth: 1,
+            },
+            Instruction::Return,
+        ])
+        .unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![
+                Statement::ContainerAdd {
+                    container: load_local(0),
+                    kind: BuildKind::Set,
+                    item: load_local(1)
+                },
+                Statement::Return(load_local(0))
+            ]
+            .into_boxed_slice()
+        );
+
+        let err = eval_ir(vec![
+            Instruction::Build {
+                kind: BuildKind::List,
+                count: 0,
+            },
+            local(1),
+            Instruction::ContainerAdd {
+                kind: BuildKind::Set,
+                depth: 1,
+            },
+        ]);
+        assert!(matches!(err, Err(EvaluationError::NotAContainer(_))));
+    }
+
+    #[test]
+    /// Test `a, *b = c`
+    fn eval_unpack() {
+        let store = |n| Instruction::Store {
+            into: UnresolvedPlace::Local(n),
+        };
+        let blocks = eval_ir(vec![
+            local(2),
+            Instruction::Unpack {
+                count: 2,
+                star: Some(1),
+            },
+            store(0),
+            store(1),
+        ])
+        .unwrap();
+        let item = |index| Expr::UnpackedItem { id: 1, index };
+        assert_eq!(
+            blocks[&0].body,
+            vec![
+                Statement::Unpack {
+                    expr: load_local(2),
+                    id: 1,
+                    count: 2,
+                    star: Some(1)
+                },
+                Statement::Store {
+                    expr: item(0),
+                    into: Place::Local(0)
+                },
+                Statement::Store {
+                    expr: item(1),
+                    into: Place::Local(1)
+                },
+            ]
+            .into_boxed_slice()
+        );
+    }
+
+    #[test]
+    /// Test `a.b(c, d=e)`
+    fn eval_call_kw() {
+        let blocks = eval_with_consts(
+            vec![
+                local(0),
+                Instruction::LoadAttr {
+                    name: 0,
+                    method: true,
+                },
+                local(1),
+                local(2),
+                Instruction::LoadConst(Constant::ByIndex(1)),
+                Instruction::Call {
+                    count: 2,
+                    receiver: true,
+                    keywords: true,
+                },
+                Instruction::Return,
+            ],
+            vec![
+                PyObject::String(Cow::Borrowed("d")),
+                PyObject::Tuple(Box::new([PyObjectIndex(0)])),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![Statement::Return(Expr::Call {
+                func: Box::new(Expr::Attribute {
+                    obj: Box::new(load_local(0)),
+                    name: 0
+                }),
+                args: Box::new([load_local(1)]),
+                keywords: Box::new([Keyword::Named {
+                    name: "d".to_string(),
+                    value: load_local(2)
+                }])
+            })]
+            .into_boxed_slice()
+        );
+
+        let err = eval_ir(vec![
+            local(0),
+            Instruction::LoadConst(Constant::Null),
+            local(1),
+            Instruction::Call {
+                count: 1,
+                receiver: true,
+                keywords: true,
+            },
+        ]);
+        assert!(matches!(
+            err,
+            Err(EvaluationError::InvalidKeywordNames(Expr::Load { .. }))
+        ));
+    }
+
+    #[test]
+    /// Test `a(*b, c, x=d, **e)`, with NULL below the callable as before 3.13
+    fn eval_call_ex() {
+        let blocks = eval_with_consts(
+            vec![
+                Instruction::LoadConst(Constant::Null),
+                local(0),
+                Instruction::Build {
+                    kind: BuildKind::List,
+                    count: 0,
+                },
+                local(1),
+                Instruction::ContainerExtend {
+                    kind: BuildKind::List,
+                    depth: 1,
+                },
+                local(2),
+                Instruction::ContainerAdd {
+                    kind: BuildKind::List,
+                    depth: 1,
+                },
+                Instruction::CallIntrinsic1(Intrinsic1::ListToTuple),
+                Instruction::LoadConst(Constant::ByIndex(0)),
+                local(3),
+                Instruction::Build {
+                    kind: BuildKind::Dict,
+                    count: 1,
+                },
+                local(4),
+                Instruction::ContainerExtend {
+                    kind: BuildKind::Dict,
+                    depth: 1,
+                },
+                Instruction::CallEx {
+                    receiver: true,
+                    kwargs: true,
+                },
+                Instruction::Return,
+            ],
+            vec![PyObject::String(Cow::Borrowed("x"))],
+        )
+        .unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![Statement::Return(Expr::Call {
+                func: Box::new(load_local(0)),
+                args: Box::new([Expr::Starred(Box::new(load_local(1))), load_local(2)]),
+                keywords: Box::new([
+                    Keyword::Named {
+                        name: "x".to_string(),
+                        value: load_local(3)
+                    },
+                    Keyword::DoubleStarred(load_local(4))
+                ])
+            })]
+            .into_boxed_slice()
+        );
+    }
+
+    #[test]
+    /// Test `for x in a: if x: return x` followed by `return None`, where the
+    /// iterator is on the stack throughout the loop
+    fn eval_for_loop() {
+        let blocks = eval_ir(vec![
+            local(0),
+            Instruction::Coercion(Coercion::Iter),
+            Instruction::ForIter { exit_target: 10 },
+            Instruction::Store {
+                into: UnresolvedPlace::Local(1),
+            },
+            local(1),
+            Instruction::Jump {
+                class: JumpClass::IfFalse,
+                target: 2,
+            },
+            local(1),
+            Instruction::Swap(2),
+            Instruction::PopIter,
+            Instruction::Return,
+            Instruction::PopIter,
+            Instruction::LoadConst(Constant::None),
+            Instruction::Return,
+        ])
+        .unwrap();
+        let mut blocks: Vec<_> = blocks.into_iter().collect();
+        blocks.sort_by_key(|(start, _)| *start);
+        assert_eq!(
+            blocks,
+            vec![
+                (
+                    0,
+                    Block {
+                        body: Box::new([]),
+                        control_flow: ControlFlow::Unconditional(2),
+                        handler: None
+                    }
+                ),
+                (
+                    2,
+                    Block {
+                        body: Box::new([]),
+                        control_flow: ControlFlow::ForEach {
+                            iter: Expr::Coercion(Coercion::Iter, Box::new(load_local(0))),
+                            id: 2,
+                            body: 3,
+                            exit: 10
+                        },
+                        handler: None
+                    }
+                ),
+                (
+                    3,
+                    Block {
+                        body: Box::new([Statement::Store {
+                            expr: Expr::ForItem { id: 2 },
+                            into: Place::Local(1)
+                        }]),
+                        control_flow: ControlFlow::CondtionalJump {
+                            if_true: 6,
+                            if_false: 2,
+                            expr: load_local(1)
+                        },
+                        handler: None
+                    }
+                ),
+                (
+                    6,
+                    Block {
+                        body: Box::new([Statement::Return(load_local(1))]),
+                        control_flow: ControlFlow::Terminates,
+                        handler: None
+                    }
+                ),
+                (
+                    10,
+                    Block {
+                        body: Box::new([Statement::Return(Expr::Constant(Constant::None))]),
+                        control_flow: ControlFlow::Terminates,
+                        handler: None
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    /// Test that `a if b else c` joins b and c, and that it is rejected when
+    /// one side leaves more on the stack than the other
+    fn eval_join() {
+        let code = |else_branch: &[Instruction]| {
+            let mut code = vec![
+                local(0),
+                Instruction::Jump {
+                    class: JumpClass::IfFalse,
+                    target: 4,
+                },
+                local(1),
+                Instruction::Jump {
+                    class: JumpClass::Always,
+                    target: 4 + else_branch.len() as u32,
+                },
+            ];
+            code.extend_from_slice(else_branch);
+            code.push(Instruction::Return);
+            code
+        };
+        let blocks = eval_ir(code(&[local(2)])).unwrap();
+        assert_eq!(
+            blocks[&5].body,
+            vec![Statement::Return(Expr::Join { block: 5, slot: 0 })].into_boxed_slice()
+        );
+
+        let err = eval_ir(code(&[local(2), local(3)]));
+        assert!(
+            matches!(err, Err(EvaluationError::InconsistentStack(6))),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    /// Test examples/eval/joins.py, where `a or b` leaves a or b on the stack,
+    /// in a loop and outside of one
+    fn eval_compiled_joins() {
+        for (version, module) in fixtures!("joins") {
+            let blocks = eval_function(version, module, "either").unwrap();
+            assert!(
+                blocks.iter().any(|(start, block)| block.body.first()
+                    == Some(&Statement::Store {
+                        expr: Expr::Join {
+                            block: *start,
+                            slot: 0
+                        },
+                        into: Place::Local(2)
+                    })),
+                "{version}: {blocks:?}"
+            );
+
+            let blocks = eval_function(version, module, "last_true").unwrap();
+            assert!(
+                blocks.values().any(|block| matches!(
+                    block.body.first(),
+                    Some(Statement::Store {
+                        expr: Expr::Join { .. },
+                        into: Place::Local(1)
+                    })
+                )),
+                "{version}: {blocks:?}"
+            );
+        }
+    }
+
+    #[test]
+    /// Test examples/eval/comprehensions.py, whose accumulators are joined by
+    /// the loop, so items are added to them by statements. Before 3.12 the
+    /// comprehensions are functions of their own
+    fn eval_compiled_comprehensions() {
+        for (version, module) in fixtures!("comprehensions") {
+            for (function, comprehension, kind) in [
+                ("doubled", "<listcomp>", BuildKind::List),
+                ("odd", "<setcomp>", BuildKind::Set),
+                ("inverted", "<dictcomp>", BuildKind::Dict),
+            ] {
+                let name = match version {
+                    PythonVersion::V3_11 => comprehension,
+                    _ => function,
+                };
+                let blocks = eval_function(version, module, name).unwrap();
+                let added = blocks
+                    .values()
+                    .flat_map(|block| &block.body)
+                    .any(|statement| match statement {
+                        Statement::ContainerAdd {
+                            container: Expr::Join { .. },
+                            kind: added,
+                            ..
+                        } => *added == kind,
+                        Statement::DictAdd {
+                            container: Expr::Join { .. },
+                            item: DictItem::Pair { .. },
+                        } => kind == BuildKind::Dict,
+                        _ => false,
+                    });
+                assert!(added, "{version} {name}: {blocks:?}");
+                assert!(
+                    blocks.values().any(|block| matches!(
+                        block.body.last(),
+                        Some(Statement::Return(Expr::Join { .. }))
+                    )),
+                    "{version} {name}: {blocks:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    /// Test `from . import a`
+    fn eval_import_from() {
+        let blocks = eval_ir(vec![
+            Instruction::LoadConst(Constant::SmallInt(1)),
+            Instruction::LoadConst(Constant::ByIndex(0)),
+            Instruction::ImportName { name: 0 },
+            Instruction::ImportFrom { name: 1 },
+            Instruction::Store {
+                into: UnresolvedPlace::Local(0),
+            },
+            Instruction::Pop,
+        ])
+        .unwrap();
+        let import = Expr::Import {
+            module: 0,
+            fromlist: Box::new(Expr::Constant(Constant::ByIndex(0))),
+            level: Box::new(Expr::Constant(Constant::SmallInt(1))),
+        };
+        assert_eq!(
+            blocks[&0].body,
+            vec![
+                Statement::Store {
+                    expr: Expr::ImportFrom {
+                        module: Box::new(import.clone()),
+                        name: 1
+                    },
+                    into: Place::Local(0)
+                },
+                Statement::Trivial(import)
+            ]
+            .into_boxed_slice()
+        );
+    }
+
+    #[test]
+    /// Test `return await a` in 3.14, where the `SEND` loop and the cleanup
+    /// after a throw both lead to `END_SEND` with the same stack
+    fn eval_await() {
+        let op = |opcode| {
+            OpcodeTable::for_version(PythonVersion::V3_14)
+                .find(opcode)
+                .unwrap()
+                .byte
+        };
+        #[rustfmt::skip]
+        let code = [
+            op(Opcode::ReturnGenerator), 0,
+            op(Opcode::PopTop), 0,
+            op(Opcode::Resume), 0,
+            op(Opcode::LoadFast), 0,
+            op(Opcode::GetAwaitable), 0,
+            op(Opcode::LoadConst), 0,
+            op(Opcode::Send), 3, 0, 0,
+            op(Opcode::YieldValue), 1,
+            op(Opcode::Resume), 3,
+            op(Opcode::JumpBackwardNoInterrupt), 5,
+            op(Opcode::EndSend), 0,
+            op(Opcode::ReturnValue), 0,
+            op(Opcode::CleanupThrow), 0,
+            op(Opcode::JumpBackwardNoInterrupt), 4,
+            op(Opcode::CallIntrinsic1), 3,
+            op(Opcode::Reraise), 1,
+        ];
+        let parsed = stack_ir::parse::parse314(&code, &[]).unwrap().instructions;
+        let blocks = eval_ir(parsed.clone()).unwrap();
+        let end_send = parsed
+            .iter()
+            .position(|instr| *instr == Instruction::EndSend)
+            .unwrap() as u32;
+        assert_eq!(
+            blocks[&end_send].body,
+            vec![Statement::Return(Expr::Await(Box::new(load_local(0))))].into_boxed_slice()
+        );
+
+        // The receiver, last sent value and exception when thrown into
+        let blocks = eval_ir(vec![
+            local(0),
+            Instruction::Coercion(Coercion::Awaitable),
+            Instruction::LoadConst(Constant::None),
+            local(1),
+            Instruction::CleanupThrow,
+            Instruction::EndSend,
+            Instruction::Return,
+        ])
+        .unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![Statement::Return(Expr::Await(Box::new(load_local(0))))].into_boxed_slice()
+        );
+    }
+
+    #[test]
+    /// Test `with a as b: b()` in 3.14, where the cleanup handler gives the
+    /// body and the calls to `__enter__` and `__exit__` are part of the With
+    fn eval_with() {
+        let table = OpcodeTable::for_version(PythonVersion::V3_14);
+        let mut code = Vec::new();
+        // The offsets of the instructions in code units, for the labels
+        let mut offsets = Vec::new();
+        for (opcode, arg) in [
+            (Opcode::Resume, 0),
+            (Opcode::LoadFast, 0),
+            (Opcode::Copy, 1),
+            (Opcode::LoadSpecial, 1),
+            (Opcode::Swap, 2),
+            (Opcode::Swap, 3),
+            (Opcode::LoadSpecial, 0),
+            (Opcode::Call, 0),
+            // L1
+            (Opcode::StoreFast, 1),
+            (Opcode::LoadFast, 1),
+            (Opcode::PushNull, 0),
+            (Opcode::Call, 0),
+            (Opcode::PopTop, 0),
+            // L2
+            (Opcode::LoadConst, 0),
+            (Opcode::LoadConst, 0),
+            (Opcode::LoadConst, 0),
+            (Opcode::Call, 3),
+            (Opcode::PopTop, 0),
+            (Opcode::LoadConst, 0),
+            (Opcode::ReturnValue, 0),
+            // L3
+            (Opcode::PushExcInfo, 0),
+            (Opcode::WithExceptStart, 0),
+            (Opcode::ToBool, 0),
+            (Opcode::PopJumpIfTrue, 2),
+            (Opcode::NotTaken, 0),
+            (Opcode::Reraise, 2),
+            (Opcode::PopTop, 0),
+            // L4
+            (Opcode::PopExcept, 0),
+            (Opcode::PopTop, 0),
+            (Opcode::PopTop, 0),
+            (Opcode::PopTop, 0),
+            (Opcode::LoadConst, 0),
+            (Opcode::ReturnValue, 0),
+            // L5
+            (Opcode::Copy, 3),
+            (Opcode::PopExcept, 0),
+            (Opcode::Reraise, 1),
+        ] {
+            let info = table.find(opcode).unwrap();
+            offsets.push((code.len() / 2) as u8);
+            code.extend([info.byte, arg]);
+            code.resize(code.len() + 2 * info.cache_entries as usize, 0);
+        }
+        let (l1, l2, l3, l4, l5) = (
+            offsets[8],
+            offsets[13],
+            offsets[20],
+            offsets[27],
+            offsets[33],
+        );
+        // L1 to L2 -> L3 [2] lasti, L3 to L4 -> L5 [4] lasti
+        let exception_table = [
+            0x80 | l1,
+            l2 - l1,
+            l3,
+            2 << 1 | 1,
+            0x80 | l3,
+            l4 - l3,
+            l5,
+            4 << 1 | 1,
+        ];
+        let parsed = stack_ir::parse::parse314(&code, &exception_table).unwrap();
+        let blocks = eval_parsed(parsed, vec![PyObject::None]).unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![Statement::With {
+                context: load_local(0),
+                id: 6,
+                target: Some(Place::Local(1)),
+                is_async: false,
+                body: 7..12
+            }]
+            .into_boxed_slice()
+        );
+        assert_eq!(
+            blocks[&7],
+            Block {
+                body: Box::new([Statement::Trivial(Expr::Call {
+                    func: Box::new(load_local(1)),
+                    args: Box::new([]),
+                    keywords: Box::new([])
+                })]),
+                control_flow: ControlFlow::Unconditional(12),
+                handler: Some(19)
+            }
+        );
+        assert_eq!(
+            blocks[&12].body,
+            vec![Statement::Return(Expr::Constant(Constant::ByIndex(0)))].into_boxed_slice()
+        );
+        // The handler calls `__exit__` with the exception, and re-raises it
+        // unless that returns true
+        let exit = Expr::WithExit {
+            exit: Box::new(Expr::SpecialMethod {
+                obj: Box::new(load_local(0)),
+                method: SpecialMethod::Exit,
+            }),
+            exception: Box::new(Expr::CaughtException { handler: 19 }),
+        };
+        assert_eq!(
+            blocks[&19].control_flow,
+            ControlFlow::CondtionalJump {
+                if_true: 24,
+                if_false: 25,
+                expr: Expr::UnaryOp(
+                    stack_ir::UnaryOp::LogicalNot,
+                    Box::new(Expr::Coercion(Coercion::Bool, Box::new(exit)))
+                )
+            }
+        );
+        assert_eq!(
+            blocks[&24].control_flow,
+            ControlFlow::Reraise(Expr::CaughtException { handler: 19 })
+        );
+    }
+
+    #[test]
+    /// Test examples/eval/withs.py, where BEFORE_WITH and BEFORE_ASYNC_WITH
+    /// enter the same With statements as the LOAD_SPECIAL sequence of 3.14, and
+    /// the cleanup calls the exit method four items down
+    fn eval_compiled_with() {
+        // The exit call the cleanup handler of the with branches on
+        fn with_exit(expr: &Expr) -> Option<&Expr> {
+            match expr {
+                Expr::WithExit { exit, .. } => Some(exit),
+                Expr::UnaryOp(_, expr) | Expr::Coercion(_, expr) | Expr::Await(expr) => {
+                    with_exit(expr)
+                }
+                _ => None,
+            }
+        }
+
+        for (version, module) in fixtures!("withs") {
+            for (name, context, target, method) in [
+                ("write", None, Some(Place::Local(2)), SpecialMethod::Exit),
+                ("locked", Some(load_local(0)), None, SpecialMethod::Exit),
+                (
+                    "fetch",
+                    Some(load_local(0)),
+                    Some(Place::Local(1)),
+                    SpecialMethod::AsyncExit,
+                ),
+            ] {
+                let blocks = eval_function(version, module, name).unwrap();
+                let Statement::With {
+                    context: with_context,
+                    target: with_target,
+                    is_async,
+                    ..
+                } = &blocks[&0].body[0]
+                else {
+                    panic!("{version} {name}: {blocks:?}")
+                };
+                if let Some(context) = &context {
+                    assert_eq!(with_context, context, "{version} {name}");
+                }
+                assert_eq!(*with_target, target, "{version} {name}");
+                assert_eq!(*is_async, method == SpecialMethod::AsyncExit);
+
+                let exit = Expr::SpecialMethod {
+                    obj: Box::new(with_context.clone()),
+                    method,
+                };
+                assert!(
+                    blocks.values().any(|block| matches!(
+                        &block.control_flow,
+                        ControlFlow::CondtionalJump { expr, .. } if with_exit(expr) == Some(&exit)
+                    )),
+                    "{version} {name}: {blocks:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    /// Test `try: a() except E: b()` in 3.14, where the handler is entered
+    /// with the exception and is itself protected by a cleanup handler
+    fn eval_try() {
+        let table = OpcodeTable::for_version(PythonVersion::V3_14);
+        let mut code = Vec::new();
+        let mut offsets = Vec::new();
+        for (opcode, arg) in [
+            (Opcode::Resume, 0),
+            (Opcode::Nop, 0),
+            // L1
+            (Opcode::LoadGlobal, 1),
+            (Opcode::Call, 0),
+            (Opcode::PopTop, 0),
+            // L2
+            (Opcode::LoadConst, 0),
+            (Opcode::ReturnValue, 0),
+            // L3
+            (Opcode::PushExcInfo, 0),
+            (Opcode::LoadGlobal, 1 << 1),
+            (Opcode::CheckExcMatch, 0),
+            (Opcode::PopJumpIfFalse, 15),
+            (Opcode::NotTaken, 0),
+            (Opcode::PopTop, 0),
+            (Opcode::LoadGlobal, 2 << 1 | 1),
+            (Opcode::Call, 0),
+            (Opcode::PopTop, 0),
+            // L4
+            (Opcode::PopExcept, 0),
+            (Opcode::LoadConst, 0),
+            (Opcode::ReturnValue, 0),
+            // L5
+            (Opcode::Reraise, 0),
+            // L6
+            (Opcode::Copy, 3),
+            (Opcode::PopExcept, 0),
+            (Opcode::Reraise, 1),
+        ] {
+            let info = table.find(opcode).unwrap();
+            offsets.push((code.len() / 2) as u8);
+            code.extend([info.byte, arg]);
+            code.resize(code.len() + 2 * info.cache_entries as usize, 0);
+        }
+        let (l1, l2, l3, l4, l5, l6) = (
+            offsets[2],
+            offsets[5],
+            offsets[7],
+            offsets[16],
+            offsets[19],
+            offsets[20],
+        );
+        // L1 to L2 -> L3 [0], L3 to L4 -> L6 [1] lasti, L5 to L6 -> L6 [1] lasti
+        #[rustfmt::skip]
+        let exception_table = [
+            0x80 | l1, l2 - l1, l3, 0,
+            0x80 | l3, l4 - l3, l6, 1 << 1 | 1,
+            0x80 | l5, l6 - l5, l6, 1 << 1 | 1,
+        ];
+        let parsed = stack_ir::parse::parse314(&code, &exception_table).unwrap();
+        let blocks = eval_parsed(parsed, vec![PyObject::None]).unwrap();
+        let global = |n| Expr::Load {
+            from: Place::Global(n),
+        };
+        let call = |func| Expr::Call {
+            func: Box::new(func),
+            args: Box::new([]),
+            keywords: Box::new([]),
+        };
+        assert_eq!(
+            blocks[&0],
+            Block {
+                body: Box::new([
+                    Statement::Try {
+                        body: 0..4,
+                        handler: 6
+                    },
+                    Statement::Trivial(call(global(0)))
+                ]),
+                control_flow: ControlFlow::Unconditional(4),
+                handler: Some(6)
+            }
+        );
+        let caught = Expr::CaughtException { handler: 6 };
+        assert_eq!(
+            blocks[&6],
+            Block {
+                body: Box::new([]),
+                control_flow: ControlFlow::CondtionalJump {
+                    if_true: 10,
+                    if_false: 18,
+                    expr: Expr::ExceptionMatches {
+                        exception: Box::new(caught.clone()),
+                        class: Box::new(global(1))
+                    }
+                },
+                handler: Some(19)
+            }
+        );
+        assert_eq!(
+            blocks[&10].body,
+            vec![Statement::Trivial(call(global(2)))].into_boxed_slice()
+        );
+        assert_eq!(blocks[&18].control_flow, ControlFlow::Reraise(caught));
+        // The cleanup restores the previous exception and re-raises
+        assert_eq!(
+            blocks[&19].control_flow,
+            ControlFlow::Reraise(Expr::CaughtException { handler: 19 })
+        );
+    }
+
+    #[test]
+    /// Test that a handler is entered with the stack of the block it protects
+    /// as it was on entry, when the block swaps the items below the depth
+    fn eval_handler_entry() {
+        let code = ParsedCode {
+            instructions: vec![
+                local(0),
+                local(1),
+                Instruction::Swap(2),
+                Instruction::Return,
+                Instruction::Pop,
+                Instruction::Return,
+            ],
+            exception_handlers: vec![ExceptionHandler {
+                start: 2,
+                end: 4,
+                target: 4,
+                depth: 2,
+                lasti: false,
+            }],
+            origins: Vec::new(),
+        };
+        let blocks = eval_parsed(code, Vec::new()).unwrap();
+        assert_eq!(
+            blocks[&4].body,
+            vec![Statement::Return(load_local(1))].into_boxed_slice()
+        );
+    }
+
+    #[test]
+    /// Test examples/eval/asynchronous.py, where the awaits pop their
+    /// awaitables before leaving the loop of sends. In 3.11 that is done by
+    /// the send itself rather than END_SEND
+    fn eval_compiled_async_for() {
+        for (version, module) in fixtures!("asynchronous") {
+            let blocks = eval_function(version, module, "total").unwrap();
+            let statements: Vec<_> = blocks.values().flat_map(|block| &block.body).collect();
+            let item = Expr::Await(Box::new(Expr::Coercion(
+                Coercion::AsyncNext,
+                Box::new(Expr::Coercion(Coercion::AsyncIter, Box::new(load_local(0)))),
+            )));
+            let sum = Expr::BinaryOp {
+                op: stack_ir::BinOp::InplaceAdd,
+                lhs: Box::new(load_local(1)),
+                rhs: Box::new(Expr::Await(Box::new(load_local(2)))),
+            };
+            for expected in [
+                Statement::Store {
+                    expr: item,
+                    into: Place::Local(2),
+                },
+                Statement::Store {
+                    expr: sum,
+                    into: Place::Local(1),
+                },
+                Statement::Return(load_local(1)),
+            ] {
+                assert!(statements.contains(&&expected), "{version}: {blocks:?}");
+            }
+        }
+    }
+
+    #[test]
+    /// Test `countdown` in examples/eval/asynchronous.py, an async generator
+    /// whose yielded values are wrapped before 3.12 by `ASYNC_GEN_WRAP`
+    fn eval_compiled_async_generator() {
+        for (version, module) in fixtures!("asynchronous") {
+            let blocks = eval_function(version, module, "countdown").unwrap();
+            let yielded = Statement::Trivial(Expr::Yield(Box::new(load_local(0))));
+            assert!(
+                blocks
+                    .values()
+                    .any(|block| block.body.first() == Some(&yielded)),
+                "{version}: {blocks:?}"
+            );
+        }
+    }
+
+    #[test]
+    /// Test the star import of examples/eval/imports.py, which is its own
+    /// opcode in 3.11 and an intrinsic after that
+    fn eval_compiled_star_import() {
+        for (version, module) in fixtures!("imports") {
+            let blocks = eval_function(version, module, "<module>").unwrap();
+            let import = Expr::Import {
+                module: 0,
+                fromlist: Box::new(Expr::Constant(Constant::ByIndex(1))),
+                level: Box::new(Expr::Constant(Constant::ByIndex(0))),
+            };
+            assert_eq!(
+                blocks[&0].body[0],
+                Statement::Trivial(Expr::CallIntrinsic1(
+                    Intrinsic1::ImportStar,
+                    Box::new(import)
+                )),
+                "{version}"
+            );
+        }
+    }
+
+    #[test]
+    /// Test examples/eval/annotations.py, where both the module and the body
+    /// of a class have annotated names
+    fn eval_compiled_annotations() {
+        for (version, module) in fixtures!("annotations") {
+            for name in ["<module>", "Point"] {
+                let blocks = eval_function(version, module, name).unwrap();
+                assert!(
+                    blocks[&0].body.contains(&Statement::SetupAnnotations),
+                    "{version} {name}: {blocks:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    /// Test examples/eval/deletes.py, which deletes a local, a global, a cell,
+    /// an attribute and a subscript in a function and a name in the module
+    fn eval_compiled_deletes() {
+        for (version, module) in fixtures!("deletes") {
+            let blocks = eval_function(version, module, "deletes").unwrap();
+            assert_eq!(
+                blocks[&0].body[2..7],
+                [
+                    Statement::Delete {
+                        place: Place::Local(0)
+                    },
+                    Statement::Delete {
+                        place: Place::Global(0)
+                    },
+                    Statement::Delete {
+                        place: Place::Cell(3)
+                    },
+                    Statement::DeleteAttr {
+                        obj: load_local(1),
+                        name: 1
+                    },
+                    Statement::DeleteSubscript {
+                        container: load_local(1),
+                        key: Expr::Constant(Constant::ByIndex(2))
+                    },
+                ],
+                "{version}"
+            );
+
+            let blocks = eval_function(version, module, "<module>").unwrap();
+            assert_eq!(
+                blocks[&0].body[2],
+                Statement::Delete {
+                    place: Place::Global(0)
+                },
+                "{version}"
+            );
+        }
+    }
+
+    #[test]
+    /// Test examples/eval/literals.py, with dicts of constant keys, an assert
+    /// and an except*
+    fn eval_compiled_literals() {
+        for (version, module) in fixtures!("literals") {
+            let region = Unmarshaller::loads(module).unwrap();
+            let keys = |items: &[DictItem]| -> Vec<String> {
+                items
+                    .iter()
+                    .map(|item| match item {
+                        DictItem::Pair {
+                            key: Expr::Object(key),
+                            ..
+                        } => match region.get(*key) {
+                            Some(PyObject::String(s)) => s.to_string(),
+                            key => panic!("{version}: {key:?}"),
+                        },
+                        item => panic!("{version}: {item:?}"),
+                    })
+                    .collect()
+            };
+
+            let blocks = eval_function(version, module, "point").unwrap();
+            let [Statement::Return(Expr::Dict(items))] = &blocks[&0].body[..] else {
+                panic!("{version}: {blocks:?}")
+            };
+            assert_eq!(keys(items), ["x", "y"], "{version}");
+            assert!(
+                matches!(&items[1], DictItem::Pair { value, .. } if *value == load_local(1)),
+                "{version}: {items:?}"
+            );
+
+            let blocks = eval_function(version, module, "<module>").unwrap();
+            let Statement::Store {
+                expr:
+                    Expr::MakeFunction {
+                        kwdefaults: Some(kwdefaults),
+                        ..
+                    },
+                ..
+            } = &blocks[&0].body[1]
+            else {
+                panic!("{version}: {blocks:?}")
+            };
+            let Expr::Dict(items) = kwdefaults.as_ref() else {
+                panic!("{version}: {kwdefaults:?}")
+            };
+            assert_eq!(keys(items), ["k", "j"], "{version}");
+
+            let blocks = eval_function(version, module, "checked").unwrap();
+            assert!(
+                blocks.values().any(|block| matches!(
+                    &block.control_flow,
+                    ControlFlow::Raise {
+                        exception: Some(Expr::Call { func, .. }),
+                        ..
+                    } if **func == Expr::Constant(Constant::Common(CommonConstant::AssertionError))
+                )),
+                "{version}: {blocks:?}"
+            );
+
+            let blocks = eval_function(version, module, "grouped").unwrap();
+            assert!(
+                blocks.values().any(|block| matches!(
+                    &block.control_flow,
+                    ControlFlow::Reraise(Expr::CallIntrinsic2(Intrinsic2::PrepReraiseStar, ..))
+                )),
+                "{version}: {blocks:?}"
+            );
+        }
+    }
+
+    #[test]
+    /// Test examples/eval/handlers.py, where a bare raise in an inner except
+    /// is handled by an outer one, whose cleanup is also reached from the
+    /// return in it
+    fn eval_compiled_nested_handlers() {
+        for (version, module) in fixtures!("handlers") {
+            let blocks = eval_function(version, module, "reraised").unwrap();
+            assert!(
+                blocks
+                    .values()
+                    .any(|block| block.body.last() == Some(&Statement::Return(load_local(3)))),
+                "{version}: {blocks:?}"
+            );
+            assert!(eval_function(version, module, "reraised_in_loop").is_ok());
+        }
+    }
+
+    #[test]
+    /// Test `raise a from b`
+    fn eval_raise() {
+        let blocks = eval_ir(vec![
+            local(0),
+            local(1),
+            Instruction::Raise { count: 2 },
+            Instruction::Raise { count: 0 },
+        ])
+        .unwrap();
+        assert_eq!(
+            blocks[&0].control_flow,
+            ControlFlow::Raise {
+                exception: Some(load_local(0)),
+                cause: Some(load_local(1))
+            }
+        );
+        // Code after a raise isn't reachable
+        assert_eq!(blocks.len(), 1);
+    }
+
+    #[test]
+    /// Test `match a: case B(c, d=e): return c`, where the subject and the
+    /// result of the match are on the stack when it fails
+    fn eval_match_class() {
+        let store = |n| Instruction::Store {
+            into: UnresolvedPlace::Local(n),
+        };
+        let blocks = eval_ir(vec![
+            local(0),
+            Instruction::Copy(1),
+            local(1),
+            Instruction::LoadConst(Constant::ByIndex(0)),
+            Instruction::MatchClass { count: 1 },
+            Instruction::Copy(1),
+            Instruction::LoadConst(Constant::None),
+            Instruction::BinaryOp(stack_ir::BinOp::Is),
+            Instruction::UnaryOp(stack_ir::UnaryOp::LogicalNot),
+            Instruction::Jump {
+                class: JumpClass::IfFalse,
+                target: 16,
+            },
+            Instruction::Unpack {
+                count: 2,
+                star: None,
+            },
+            store(2),
+            store(4),
+            Instruction::Pop,
+            local(2),
+            Instruction::Return,
+            Instruction::Pop,
+            Instruction::Pop,
+            Instruction::LoadConst(Constant::None),
+            Instruction::Return,
+        ])
+        .unwrap();
+        let matched = Expr::MatchClass {
+            subject: Box::new(load_local(0)),
+            class: Box::new(load_local(1)),
+            count: 1,
+            keywords: Box::new(Expr::Constant(Constant::ByIndex(0))),
+        };
+        assert_eq!(
+            blocks[&10].body,
+            vec![
+                Statement::Unpack {
+                    expr: matched.clone(),
+                    id: 10,
+                    count: 2,
+                    star: None
+                },
+                Statement::Store {
+                    expr: Expr::UnpackedItem { id: 10, index: 0 },
+                    into: Place::Local(2)
+                },
+                Statement::Store {
+                    expr: Expr::UnpackedItem { id: 10, index: 1 },
+                    into: Place::Local(4)
+                },
+                Statement::Trivial(load_local(0)),
+                Statement::Return(load_local(2)),
+            ]
+            .into_boxed_slice()
+        );
+        assert_eq!(
+            blocks[&16].body,
+            vec![
+                Statement::Trivial(matched),
+                Statement::Trivial(load_local(0)),
+                Statement::Return(Expr::Constant(Constant::None)),
+            ]
+            .into_boxed_slice()
+        );
+    }
+
+    #[test]
+    /// Test examples/eval/patterns.py, where a case fails with either the match
+    /// result or an item of it on the stack, which are joined before the next
+    /// case pops them
+    fn eval_compiled_match_class() {
+        for (version, module) in fixtures!("patterns") {
+            let blocks = eval_function(version, module, "locate").unwrap();
+            let statements: Vec<_> = blocks.values().flat_map(|block| &block.body).collect();
+            for (index, local) in [(1, 1), (0, 2)] {
+                assert!(
+                    statements.iter().any(|statement| matches!(
+                        statement,
+                        Statement::Store {
+                            expr: Expr::UnpackedItem { index: i, .. },
+                            into: Place::Local(l),
+                        } if *i == index && *l == local
+                    )),
+                    "{version}: {blocks:?}"
+                );
+            }
+            let joined = blocks
+                .iter()
+                .filter(|(start, block)| {
+                    matches!(
+                        block.body.first(),
+                        Some(Statement::Trivial(Expr::Join { block, .. })) if block == *start
+                    )
+                })
+                .count();
+            assert_eq!(joined, 2, "{version}: {blocks:?}");
+        }
+    }
+
+    #[test]
+    /// Test `f"a{b!r:>{c}}d{e}"` in 3.14, and the t-string `t"{a}"`
+    fn eval_formatted_string() {
+        let op = |opcode| {
+            OpcodeTable::for_version(PythonVersion::V3_14)
+                .find(opcode)
+                .unwrap()
+                .byte
+        };
+        #[rustfmt::skip]
+        let code = [
+            op(Opcode::LoadConst), 0,
+            op(Opcode::LoadFast), 1,
+            op(Opcode::ConvertValue), 2,
+            op(Opcode::LoadConst), 1,
+            op(Opcode::LoadFast), 2,
+            op(Opcode::FormatSimple), 0,
+            op(Opcode::BuildString), 2,
+            op(Opcode::FormatWithSpec), 0,
+            op(Opcode::LoadConst), 2,
+            op(Opcode::LoadFast), 4,
+            op(Opcode::FormatSimple), 0,
+            op(Opcode::BuildString), 4,
+            op(Opcode::ReturnValue), 0,
+        ];
+        let parsed = stack_ir::parse::parse314(&code, &[]).unwrap().instructions;
+        let blocks = eval_ir(parsed).unwrap();
+        let constant = |n| Expr::Constant(Constant::ByIndex(n));
+        let formatted = |value, conversion, spec| Expr::FormattedValue {
+            value: Box::new(value),
+            conversion,
+            spec,
+        };
+        assert_eq!(
+            blocks[&0].body,
+            vec![Statement::Return(Expr::FormattedString(Box::new([
+                constant(0),
+                formatted(
+                    load_local(1),
+                    Some(stack_ir::Conversion::Repr),
+                    Some(Box::new(Expr::FormattedString(Box::new([
+                        constant(1),
+                        formatted(load_local(2), None, None)
+                    ]))))
+                ),
+                constant(2),
+                formatted(load_local(4), None, None)
+            ])))]
+            .into_boxed_slice()
+        );
+
+        let blocks = eval_ir(vec![
+            Instruction::LoadConst(Constant::ByIndex(0)),
+            local(0),
+            Instruction::LoadConst(Constant::ByIndex(1)),
+            Instruction::BuildInterpolation {
+                conversion: None,
+                spec: false,
+            },
+            Instruction::Build {
+                kind: BuildKind::Tuple,
+                count: 1,
+            },
+            Instruction::BuildTemplate,
+            Instruction::Return,
+        ])
+        .unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![Statement::Return(Expr::Template {
+                strings: Box::new(constant(0)),
+                interpolations: Box::new(Expr::Tuple(Box::new([Expr::Interpolation {
+                    value: Box::new(load_local(0)),
+                    expression: Box::new(constant(1)),
+                    conversion: None,
+                    spec: None
+                }])))
+            })]
+            .into_boxed_slice()
+        );
+    }
+
+    #[test]
+    /// Test the inner function of
+    /// ```python
+    /// def f(x):
+    ///     def g(y):
+    ///         return x+y
+    ///     return g
+    /// ```
+    /// where x is a free variable, and f, where x is a cell in the closure of g
+    fn eval_closure() {
+        let region = Unmarshaller::loads(CLOSURE).unwrap();
+        let Some(PyObject::Code(code)) = region.first() else {
+            panic!("Expected a code object")
+        };
+        let code = code.construct(&region).unwrap();
+        assert_eq!(code.local_name(1, &region), Some("x"));
+        assert!(code.local_kind(1, &region).unwrap().is_free());
+        let blocks = eval(PythonVersion::V3_14, code, &region).unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![Statement::Return(Expr::BinaryOp {
+                op: stack_ir::BinOp::Add,
+                lhs: Box::new(Expr::Load {
+                    from: Place::Cell(1)
+                }),
+                rhs: Box::new(load_local(0))
+            })]
+            .into_boxed_slice()
+        );
+
+        let region = Unmarshaller::loads(NESTED).unwrap();
+        let Some(PyObject::Code(code)) = region.first() else {
+            panic!("Expected a code object")
+        };
+        let code = code.construct(&region).unwrap();
+        assert!(code.local_kind(0, &region).unwrap().is_cell());
+        let g = code.constant(0, &region).unwrap();
+        let blocks = eval(PythonVersion::V3_14, code, &region).unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![
+                Statement::Store {
+                    expr: Expr::MakeFunction {
+                        code: g,
+                        defaults: None,
+                        kwdefaults: None,
+                        annotations: None,
+                        annotate: None,
+                        closure: Some(Box::new(Expr::Tuple(Box::new([Expr::Load {
+                            from: Place::Cell(0)
+                        }]))))
+                    },
+                    into: Place::Local(1)
+                },
+                Statement::Return(load_local(1))
+            ]
+            .into_boxed_slice()
+        );
+    }
+
+    #[test]
+    /// Test the two conditional definitions of f in examples/initial.py, whose
+    /// code objects can be evaluated in turn
+    fn eval_make_function() {
+        let region =
+            Unmarshaller::loads(&include_bytes!("../../examples/initial.pyc")[16..]).unwrap();
+        let Some(PyObject::Code(code)) = region.first() else {
+            panic!("Expected a code object")
+        };
+        let blocks = eval(
+            PythonVersion::V3_14,
+            code.construct(&region).unwrap(),
+            &region,
+        )
+        .unwrap();
+        for (block, first_line) in [(10, 2), (14, 5)] {
+            let [
+                Statement::Store {
+                    expr:
+                        Expr::MakeFunction {
+                            code,
+                            defaults: None,
+                            kwdefaults: None,
+                            annotations: None,
+                            annotate: None,
+                            closure: None,
+                        },
+                    into: Place::Global(2),
+                },
+            ] = &*blocks[&block].body
+            else {
+                panic!("Expected a definition of f, got {:?}", blocks[&block])
+            };
+            let Some(PyObject::Code(f)) = region.get(*code) else {
+                panic!("Expected a code object")
+            };
+            assert_eq!(f.first_line_no, first_line);
+            let f = f.construct(&region).unwrap();
+            assert_eq!(f.local_name(0, &region), Some("x"));
+            assert!(eval(PythonVersion::V3_14, f, &region).is_ok());
+        }
+
+        let Some(PyObject::Code(code)) = region.first() else {
+            unreachable!()
+        };
+        let err = eval(
+            PythonVersion::V3_10,
+            code.construct(&region).unwrap(),
+            &region,
+        );
+        assert!(matches!(
+            err,
+            Err(EvaluationError::UnsupportedVersion(PythonVersion::V3_10))
+        ));
+
+        let err = eval_ir(vec![local(0), Instruction::MakeFunction]);
+        assert!(matches!(err, Err(EvaluationError::NotACodeObject(_))));
+    }
+
+    #[test]
+    /// Test
+    /// ```python
+    /// @c
+    /// class A(a, metaclass=b):
+    ///     pass
+    /// ```
+    fn eval_class_def() {
+        let blocks = eval_with_consts(
+            vec![
+                local(2),
+                Instruction::LoadBuildClass,
+                Instruction::LoadConst(Constant::Null),
+                Instruction::LoadConst(Constant::ByIndex(0)),
+                Instruction::MakeFunction,
+                Instruction::LoadConst(Constant::ByIndex(1)),
+                local(0),
+                local(1),
+                Instruction::LoadConst(Constant::ByIndex(3)),
+                Instruction::Call {
+                    count: 4,
+                    receiver: true,
+                    keywords: true,
+                },
+                Instruction::Call {
+                    count: 0,
+                    receiver: true,
+                    keywords: false,
+                },
+                Instruction::Store {
+                    into: UnresolvedPlace::Local(3),
+                },
+            ],
+            vec![
+                PyObject::Code(empty_code(PyObjectIndex(3), PyObjectIndex(1))),
+                PyObject::String(Cow::Borrowed("A")),
+                PyObject::String(Cow::Borrowed("metaclass")),
+                PyObject::Tuple(Box::new([PyObjectIndex(2)])),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![
+                Statement::ClassDef {
+                    name: "A".to_string(),
+                    bases: Box::new([load_local(0)]),
+                    keywords: Box::new([Keyword::Named {
+                        name: "metaclass".to_string(),
+                        value: load_local(1)
+                    }]),
+                    body_code: PyObjectIndex(0),
+                    id: 9
+                },
+                Statement::Store {
+                    expr: Expr::Call {
+                        func: Box::new(load_local(2)),
+                        args: Box::new([Expr::Class { id: 9 }]),
+                        keywords: Box::new([])
+                    },
+                    into: Place::Local(3)
+                }
+            ]
+            .into_boxed_slice()
+        );
+
+        let err = eval_ir(vec![
+            Instruction::LoadBuildClass,
+            Instruction::LoadConst(Constant::Null),
+            local(0),
+            local(1),
+            Instruction::Call {
+                count: 2,
+                receiver: true,
+                keywords: false,
+            },
+        ]);
+        assert!(matches!(err, Err(EvaluationError::InvalidClassDef(_))));
+    }
+
+    #[test]
+    /// Test `b = yield a` in an async generator
+    fn eval_async_yield() {
+        let blocks = eval_ir(vec![
+            local(0),
+            Instruction::CallIntrinsic1(Intrinsic1::AsyncGenWrap),
+            Instruction::Yield,
+            Instruction::Store {
+                into: UnresolvedPlace::Local(1),
+            },
+        ])
+        .unwrap();
+        assert_eq!(
+            blocks[&0].body,
+            vec![Statement::Store {
+                expr: Expr::Yield(Box::new(load_local(0))),
+                into: Place::Local(1)
+            }]
+            .into_boxed_slice()
+        );
+    }
+}
@@ -7,19 +7,145 @@
 //! between uses of a variable
 
 // TODO: Move these out to a common core
-use crate::stack_ir::{BinOp, Coercion, Constant, UnaryOp, UnresolvedPlace};
+use crate::{
+    objects::PyObjectIndex,
+    stack_ir::{
+        BinOp, BuildKind, Coercion, Constant, Conversion, Intrinsic1, Intrinsic2, SpecialMethod,
+        UnaryOp, UnresolvedPlace,
+    },
+};
+use std::ops::Range;
 
 pub mod eval;
+pub use eval::eval;
 
 // I need to figure out a nice way to handle this that doesn't require so much
 // cloning. Some sort of interning I guess
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr {
     // Primitive values
     Constant(Constant),
+    // An object in the region that isn't a constant of the code object itself,
+    // like an item of a constant tuple of dict keys
+    Object(PyObjectIndex),
     Load {
         from: Place,
     },
+    // Name is an index into the names of the code object
+    Attribute {
+        obj: Box<Expr>,
+        name: u32,
+    },
+    // A special method looked up on the type of the object
+    SpecialMethod {
+        obj: Box<Expr>,
+        method: SpecialMethod,
+    },
+
+    // `__import__` of the module with this name. Names are indices into the
+    // names of the code object
+    Import {
+        module: u32,
+        fromlist: Box<Expr>,
+        level: Box<Expr>,
+    },
+    ImportFrom {
+        module: Box<Expr>,
+        name: u32,
+    },
+
+    // An item left on the stack by the Unpack statement with this id
+    UnpackedItem {
+        id: u32,
+        index: u32,
+    },
+
+    // The item of the ForEach loop whose ForIter instruction has this index
+    ForItem {
+        id: u32,
+    },
+    // The item at this index of the stack on entry to the block, where the
+    // paths into the block leave different things there, like the two sides
+    // of `a or b` or the accumulator of a comprehension
+    Join {
+        block: u32,
+        slot: u32,
+    },
+
+    // The result of entering the context of the With statement with this id,
+    // for `as` targets that aren't a variable
+    WithEnter {
+        id: u32,
+    },
+    // The result of calling `__exit__` with the exception being handled
+    WithExit {
+        exit: Box<Expr>,
+        exception: Box<Expr>,
+    },
+    // The exception that was being handled before the one being handled now
+    PreviousException,
+    // The namespace of a class body or module
+    Locals,
+    // The `__build_class__` builtin, calls of which are class definitions
+    BuildClass,
+    // The class defined by the ClassDef statement with this id
+    Class {
+        id: u32,
+    },
+    // The exception caught by the handler at this index
+    CaughtException {
+        handler: u32,
+    },
+    // The offset of the instruction that raised, which some handlers push
+    // below the exception to re-raise it from there
+    Lasti,
+    // Whether the exception is an instance of the class, for `except`
+    ExceptionMatches {
+        exception: Box<Expr>,
+        class: Box<Expr>,
+    },
+    // The parts of an exception group that do and don't match the class, for
+    // `except*`
+    ExceptionGroupMatch {
+        exception: Box<Expr>,
+        class: Box<Expr>,
+    },
+    ExceptionGroupRest {
+        exception: Box<Expr>,
+        class: Box<Expr>,
+    },
+
+    // Built objects, items of sequences and sets can be starred
+    Tuple(Box<[Expr]>),
+    List(Box<[Expr]>),
+    Set(Box<[Expr]>),
+    Dict(Box<[DictItem]>),
+    Slice {
+        start: Box<Expr>,
+        stop: Box<Expr>,
+        step: Option<Box<Expr>>,
+    },
+    // The pieces of an f-string are constant strings and formatted values
+    FormattedString(Box<[Expr]>),
+    FormattedValue {
+        value: Box<Expr>,
+        conversion: Option<Conversion>,
+        spec: Option<Box<Expr>>,
+    },
+    // t-strings, where expression is the source text of the interpolated
+    // expression, and strings and interpolations are tuples
+    Interpolation {
+        value: Box<Expr>,
+        expression: Box<Expr>,
+        conversion: Option<Conversion>,
+        spec: Option<Box<Expr>>,
+    },
+    Template {
+        strings: Box<Expr>,
+        interpolations: Box<Expr>,
+    },
+    Starred(Box<Expr>),
 
     // Operation results
     UnaryOp(UnaryOp, Box<Expr>),
@@ -29,32 +155,170 @@ pub enum Expr {
         rhs: Box<Expr>,
     },
     Coercion(Coercion, Box<Expr>),
-    MakeFunction(Box<Expr>),
+    // A function made from the code object at this index of the region, with
+    // the attributes set by [`FunctionAttribute`](crate::stack_ir::FunctionAttribute)
+    MakeFunction {
+        code: PyObjectIndex,
+        defaults: Option<Box<Expr>>,
+        kwdefaults: Option<Box<Expr>>,
+        annotations: Option<Box<Expr>>,
+        annotate: Option<Box<Expr>>,
+        closure: Option<Box<Expr>>,
+    },
 
-    // Function calls
+    // Function calls, `*args` are starred positional arguments. The receiver
+    // of method calls is the object of the attribute that is called
     Call {
         func: Box<Expr>,
-        receiver: Box<Expr>,
         args: Box<[Expr]>,
+        keywords: Box<[Keyword]>,
+    },
+    CallIntrinsic1(Intrinsic1, Box<Expr>),
+    CallIntrinsic2(Intrinsic2, Box<Expr>, Box<Expr>),
+
+    // Pattern matching. MatchClass is a tuple of the count positional
+    // attributes and those named by keywords, and MatchKeys a tuple of the
+    // values of the keys, or None if they don't match
+    MatchClass {
+        subject: Box<Expr>,
+        class: Box<Expr>,
+        count: u32,
+        keywords: Box<Expr>,
     },
+    MatchMapping(Box<Expr>),
+    MatchSequence(Box<Expr>),
+    MatchKeys {
+        subject: Box<Expr>,
+        keys: Box<Expr>,
+    },
+    Len(Box<Expr>),
+
+    // Generators, the value of a yield is whatever is sent to the generator
+    Yield(Box<Expr>),
+    YieldFrom(Box<Expr>),
+    Await(Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Keyword {
+    Named { name: String, value: Expr },
+    DoubleStarred(Expr),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DictItem {
+    Pair { key: Expr, value: Expr },
+    DoubleStarred(Expr),
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Statement {
     Trivial(Expr),
-    Store { expr: Expr, into: Place },
+    Store {
+        expr: Expr,
+        into: Place,
+    },
+    StoreAttr {
+        expr: Expr,
+        obj: Expr,
+        name: u32,
+    },
+    DeleteAttr {
+        obj: Expr,
+        name: u32,
+    },
+    StoreSubscript {
+        expr: Expr,
+        container: Expr,
+        key: Expr,
+    },
+    DeleteSubscript {
+        container: Expr,
+        key: Expr,
+    },
+    // Appends the item to a list or adds it to a set that isn't a literal,
+    // like the accumulator of a comprehension once the loop joins it. Starred
+    // items extend the container
+    ContainerAdd {
+        container: Expr,
+        kind: BuildKind,
+        item: Expr,
+    },
+    // Sets the key of a dict that isn't a literal, or updates it with a double
+    // starred item
+    DictAdd {
+        container: Expr,
+        item: DictItem,
+    },
+    // Unpacks the sequence into count items, pushed as [`Expr::UnpackedItem`]s
+    // with this id, which is the index of the instruction it came from. If star
+    // is set, the item at that index is a list of the remaining items
+    Unpack {
+        expr: Expr,
+        id: u32,
+        count: u32,
+        star: Option<u32>,
+    },
+    // `with context as target:`, where body is the range of instructions in
+    // the with. The id is the index of the instruction that enters it, and
+    // the calls to `__enter__` and `__exit__` aren't statements of their own
+    With {
+        context: Expr,
+        id: u32,
+        target: Option<Place>,
+        is_async: bool,
+        body: Range<u32>,
+    },
+    // `try:`, where body is the range of instructions whose exceptions are
+    // handled by the handler at this index, which covers every `except` and
+    // `finally` of it
+    Try {
+        body: Range<u32>,
+        handler: u32,
+    },
+    // `del place`, which unbinds it. This kills whatever was stored there
+    // before, like a Store does, but loads after it raise until the next one
+    Delete {
+        place: Place,
+    },
+    // `class name(*bases, **keywords):`, whose body is the code object at this
+    // index of the region. The class is pushed as an [`Expr::Class`] with this
+    // id, which is the index of the call that builds it, to be decorated and
+    // stored
+    ClassDef {
+        name: String,
+        bases: Box<[Expr]>,
+        keywords: Box<[Keyword]>,
+        body_code: PyObjectIndex,
+        id: u32,
+    },
+    // The start of a module or class body with annotated names, which makes
+    // the `__annotations__` dict that they are then stored into
+    SetupAnnotations,
     Return(Expr),
-    If { expr: Expr, target: u32 },
-    Jump { target: u32 },
+    If {
+        expr: Expr,
+        target: u32,
+    },
+    Jump {
+        target: u32,
+    },
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
     pub body: Box<[Statement]>,
     pub control_flow: ControlFlow,
+    // The handler that exceptions raised in this block jump to
+    pub handler: Option<u32>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ControlFlow {
     // The end of the block is an unconditional jump or "falls through" to the
     // the next block
@@ -65,11 +329,29 @@ pub enum ControlFlow {
         if_false: u32,
         expr: Expr,
     },
+    // The block ends by getting the next item of the iterator, which is left
+    // on the stack, jumping to body with the item pushed as [`Expr::ForItem`],
+    // or to exit when the iterator is exhausted
+    ForEach {
+        iter: Expr,
+        id: u32,
+        body: u32,
+        exit: u32,
+    },
+    // The block ends by raising the exception, or re-raising the exception
+    // being handled if it's None
+    Raise {
+        exception: Option<Expr>,
+        cause: Option<Expr>,
+    },
+    // The block ends by re-raising this exception
+    Reraise(Expr),
     // This block either returns or contains the final instruction
     Terminates,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Place {
     Local(u32),
     Global(u32),
@@ -0,0 +1,773 @@
+//! A disassembler that produces the same text as CPython's `dis.dis` (and so
+//! `python -m dis`) for the version that the code object was compiled by, so
+//! that our view of some bytecode can be diffed against CPython's directly
+//!
+//! The things that can't match are the address in the repr of nested code
+//! objects (`<code object f at 0x..., file "f.py", line 1>`), which is made up
+//! from the code object's index in the region instead, and the order of the
+//! items in frozensets, which CPython shows in hash order rather than the order
+//! they were marshalled in
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    objects::{CodeObject, CodeObjectConstructor, PyObject, PyObjectIndex, PyObjectRegion},
+    opcode::{
+        ArgKind, DecodeError, Opcode, OpcodeInfo, OpcodeTable, decode, parse_exception_table,
+    },
+    version::PythonVersion,
+};
+
+/// The width that opnames are padded to, `_OPNAME_WIDTH` in `dis.py`
+const OPNAME_WIDTH: usize = 20;
+/// The width that arguments are right aligned to, `_OPARG_WIDTH` in `dis.py`
+const OPARG_WIDTH: usize = 5;
+/// What's shown in the line number column for an instruction without a line
+/// number (since 3.13)
+const NO_LINENO: &str = "  --";
+
+const CMP_OP: [&str; 6] = ["<", "<=", "==", "!=", ">", ">="];
+/// `_nb_ops` from `opcode.py`, `NB_SUBSCR` was added in 3.14
+const NB_OPS: [&str; 27] = [
+    "+", "&", "//", "<<", "@", "*", "%", "|", "**", ">>", "-", "/", "^", "+=", "&=", "//=", "<<=",
+    "@=", "*=", "%=", "|=", "**=", ">>=", "-=", "/=", "^=", "[]",
+];
+const INTRINSIC_1_DESCS: [&str; 12] = [
+    "INTRINSIC_1_INVALID",
+    "INTRINSIC_PRINT",
+    "INTRINSIC_IMPORT_STAR",
+    "INTRINSIC_STOPITERATION_ERROR",
+    "INTRINSIC_ASYNC_GEN_WRAP",
+    "INTRINSIC_UNARY_POSITIVE",
+    "INTRINSIC_LIST_TO_TUPLE",
+    "INTRINSIC_TYPEVAR",
+    "INTRINSIC_PARAMSPEC",
+    "INTRINSIC_TYPEVARTUPLE",
+    "INTRINSIC_SUBSCRIPT_GENERIC",
+    "INTRINSIC_TYPEALIAS",
+];
+const INTRINSIC_2_DESCS: [&str; 6] = [
+    "INTRINSIC_2_INVALID",
+    "INTRINSIC_PREP_RERAISE_STAR",
+    "INTRINSIC_TYPEVAR_WITH_BOUND",
+    "INTRINSIC_TYPEVAR_WITH_CONSTRAINTS",
+    "INTRINSIC_SET_FUNCTION_TYPE_PARAMS",
+    "INTRINSIC_SET_TYPEPARAM_DEFAULT",
+];
+/// The flags of `MAKE_FUNCTION` (`SET_FUNCTION_ATTRIBUTE` since 3.13),
+/// `annotate` was added in 3.14
+const FUNCTION_ATTR_FLAGS: [&str; 5] = [
+    "defaults",
+    "kwdefaults",
+    "annotations",
+    "closure",
+    "annotate",
+];
+/// The conversions of `FORMAT_VALUE` (`CONVERT_VALUE` since 3.13)
+const CONVERSIONS: [&str; 4] = ["", "str", "repr", "ascii"];
+/// How `dis` shows the objects loaded by `LOAD_COMMON_CONSTANT`
+const COMMON_CONSTANTS: [&str; 5] = [
+    "AssertionError",
+    "NotImplementedError",
+    "tuple",
+    "<built-in function all>",
+    "<built-in function any>",
+];
+const SPECIAL_METHOD_NAMES: [&str; 4] = ["__enter__", "__exit__", "__aenter__", "__aexit__"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisassembleError {
+    /// Only code objects from 3.11 onwards can be unmarshalled, so earlier
+    /// versions can't be disassembled
+    UnsupportedVersion(PythonVersion),
+    /// The bytecode couldn't be decoded
+    Decode(DecodeError),
+    /// The argument of the instruction at this offset is out of range for
+    /// whatever it refers to (a constant, name, local, etc.)
+    ArgOutOfRange { offset: usize, arg: u32 },
+}
+
+/// Disassemble a code object and (like `dis.dis`) every code object in its
+/// constants, recursively
+pub fn disassemble(
+    version: PythonVersion,
+    code: &CodeObject,
+    region: &PyObjectRegion,
+) -> Result<String, DisassembleError> {
+    if version < PythonVersion::V3_11 {
+        return Err(DisassembleError::UnsupportedVersion(version));
+    }
+    let table = OpcodeTable::for_version(version);
+    let mut out = String::new();
+    disassemble_recursive(table, code.0, region, &mut out)?;
+    Ok(out)
+}
+
+fn disassemble_recursive(
+    table: &OpcodeTable,
+    code: &CodeObjectConstructor,
+    region: &PyObjectRegion,
+    out: &mut String,
+) -> Result<(), DisassembleError> {
+    disassemble_code(table, code, region, out)?;
+    for idx in tuple(region, code.consts) {
+        if let PyObject::Code(nested) = &region[*idx] {
+            out.push('\n');
+            out.push_str(&format!(
+                "Disassembly of {}:\n",
+                code_repr(*idx, nested, region)
+            ));
+            disassemble_recursive(table, nested, region, out)?;
+        }
+    }
+    Ok(())
+}
+
+struct Instruction<'t> {
+    offset: usize,
+    info: &'t OpcodeInfo,
+    arg: Option<u32>,
+    jump_target: Option<usize>,
+}
+
+fn disassemble_code(
+    table: &OpcodeTable,
+    code: &CodeObjectConstructor,
+    region: &PyObjectRegion,
+    out: &mut String,
+) -> Result<(), DisassembleError> {
+    let version = table.version();
+    let bytecode = bytes(region, code.code);
+    let instructions = decode_with_extended_args(table, bytecode)?;
+
+    let lines = co_lines(bytes(region, code.line_table), code.first_line_no);
+    let line_starts = find_line_starts(version, &lines);
+    let exception_entries = parse_exception_table(bytes(region, code.exception_table));
+
+    let mut labels = BTreeSet::new();
+    labels.extend(instructions.iter().filter_map(|i| i.jump_target));
+    for entry in &exception_entries {
+        if version >= PythonVersion::V3_13 {
+            labels.extend([entry.start, entry.end, entry.target]);
+        } else if entry.start < entry.end {
+            labels.insert(entry.target);
+        }
+    }
+    let labels: HashMap<usize, usize> = labels.into_iter().zip(1..).collect();
+
+    let lineno_width = lineno_width(version, &line_starts);
+    // Before 3.13 offsets are shown and jump targets are marked with `>>`,
+    // since then jump targets are labelled and jumps refer to labels
+    let label_width = 4 + labels.len().to_string().len();
+    let offset_width = bytecode.len().saturating_sub(2).max(9999).to_string().len();
+
+    for instruction in &instructions {
+        let starts_line = line_starts.get(&instruction.offset);
+        if lineno_width > 0 && starts_line.is_some() && instruction.offset > 0 {
+            out.push('\n');
+        }
+
+        let mut fields = Vec::new();
+        if lineno_width > 0 {
+            fields.push(match starts_line {
+                Some(Some(line)) => format!("{line:>lineno_width$}"),
+                Some(None) => format!("{NO_LINENO:>lineno_width$}"),
+                None => " ".repeat(lineno_width),
+            });
+        }
+        let name = instruction.info.opcode.name();
+        let arg_width = if version >= PythonVersion::V3_13 {
+            fields.push(match labels.get(&instruction.offset) {
+                Some(label) => format!("{:>label_width$}", format!("L{label}:")),
+                None => " ".repeat(label_width),
+            });
+            fields.push("   ".into());
+            // Long opnames overflow into the space for the argument
+            OPARG_WIDTH.saturating_sub(name.len().saturating_sub(OPNAME_WIDTH))
+        } else {
+            fields.push("   ".into());
+            let is_jump_target = labels.contains_key(&instruction.offset);
+            fields.push(if is_jump_target { ">>" } else { "  " }.into());
+            fields.push(format!("{:>offset_width$}", instruction.offset));
+            OPARG_WIDTH
+        };
+        fields.push(format!("{name:OPNAME_WIDTH$}"));
+        if let Some(arg) = instruction.arg {
+            fields.push(format!("{arg:>arg_width$}"));
+            let argrepr = argrepr(version, instruction, code, region, &labels)?;
+            if !argrepr.is_empty() {
+                fields.push(format!("({argrepr})"));
+            }
+        }
+        out.push_str(fields.join(" ").trim_end());
+        out.push('\n');
+    }
+
+    if !exception_entries.is_empty() {
+        out.push_str("ExceptionTable:\n");
+        for entry in &exception_entries {
+            let lasti = if entry.lasti { " lasti" } else { "" };
+            let depth = entry.depth;
+            if version >= PythonVersion::V3_13 {
+                let (start, end, target) = (
+                    labels[&entry.start],
+                    labels[&entry.end],
+                    labels[&entry.target],
+                );
+                out.push_str(&format!(
+                    "  L{start} to L{end} -> L{target} [{depth}]{lasti}\n"
+                ));
+            } else {
+                let (start, end, target) = (entry.start, entry.end - 2, entry.target);
+                out.push_str(&format!(
+                    "  {start} to {end} -> {target} [{depth}]{lasti}\n"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decode bytecode into instructions, which (like in `dis`) includes the
+/// `EXTENDED_ARG`s that [`decode`] folds into the following instruction
+fn decode_with_extended_args<'t>(
+    table: &'t OpcodeTable,
+    code: &[u8],
+) -> Result<Vec<Instruction<'t>>, DisassembleError> {
+    let info = |opcode| {
+        table
+            .find(opcode)
+            .expect("Decoded opcodes should be in the table they were decoded with")
+    };
+    let mut instructions = Vec::new();
+    for raw in decode(table.version(), code) {
+        let raw = raw.map_err(DisassembleError::Decode)?;
+        let prefix_len = raw.extended_arg_prefix_len as u32;
+        for i in 0..prefix_len {
+            instructions.push(Instruction {
+                offset: raw.start_offset() + 2 * i as usize,
+                info: info(Opcode::ExtendedArg),
+                arg: Some(raw.arg >> (8 * (prefix_len - i))),
+                jump_target: None,
+            });
+        }
+        let info = info(raw.opcode);
+        instructions.push(Instruction {
+            offset: raw.offset,
+            info,
+            arg: info.has_arg.then_some(raw.arg),
+            jump_target: raw.jump_target,
+        });
+    }
+    Ok(instructions)
+}
+
+/// The human readable description of an instruction's argument, that's shown
+/// in brackets after it
+fn argrepr(
+    version: PythonVersion,
+    instruction: &Instruction,
+    code: &CodeObjectConstructor,
+    region: &PyObjectRegion,
+    labels: &HashMap<usize, usize>,
+) -> Result<String, DisassembleError> {
+    use Opcode::*;
+    let Some(arg) = instruction.arg else {
+        return Ok(String::new());
+    };
+    let out_of_range = DisassembleError::ArgOutOfRange {
+        offset: instruction.offset,
+        arg,
+    };
+    let lookup = |items: &[&str], i: u32| {
+        items
+            .get(i as usize)
+            .map(|s| s.to_string())
+            .ok_or(out_of_range.clone())
+    };
+    let name = |i: u32| match tuple(region, code.names).get(i as usize) {
+        Some(idx) => Ok(string(region, *idx).to_string()),
+        None => Err(out_of_range.clone()),
+    };
+    let local = |i: u32| match tuple(region, code.locals_plus_names).get(i as usize) {
+        Some(idx) => Ok(string(region, *idx).to_string()),
+        None => Err(out_of_range.clone()),
+    };
+    // Before 3.13 the NULL that's pushed goes first, since then it goes after
+    let with_null = |name: String, null: &str| match arg & 1 {
+        0 => name,
+        _ if version >= PythonVersion::V3_13 => format!("{name} + {null}"),
+        _ => format!("{null} + {name}"),
+    };
+
+    let opcode = instruction.info.opcode;
+    Ok(match (opcode, instruction.info.arg_kind) {
+        (LoadGlobal, _) => with_null(name(arg >> 1)?, "NULL"),
+        (LoadAttr, _) if version >= PythonVersion::V3_12 => with_null(name(arg >> 1)?, "NULL|self"),
+        (LoadSuperAttr, _) => with_null(name(arg >> 2)?, "NULL|self"),
+        // 3.11 only shows the constant of LOAD_CONST, not KW_NAMES
+        (op, ArgKind::Const) if version == PythonVersion::V3_11 && op != LoadConst => String::new(),
+        (_, ArgKind::Const) => match tuple(region, code.consts).get(arg as usize) {
+            Some(idx) => object_repr(*idx, region),
+            None => return Err(out_of_range),
+        },
+        (_, ArgKind::Name) => name(arg)?,
+        (_, ArgKind::Jump) => {
+            let Some(target) = instruction.jump_target else {
+                unreachable!("The decoder gives every jump opcode a target")
+            };
+            if version >= PythonVersion::V3_13 {
+                let preposition = if opcode == EndAsyncFor { "from" } else { "to" };
+                format!("{preposition} L{}", labels[&target])
+            } else {
+                format!("to {target}")
+            }
+        }
+        (
+            LoadFastLoadFast
+            | LoadFastBorrowLoadFastBorrow
+            | StoreFastLoadFast
+            | StoreFastStoreFast,
+            _,
+        ) => {
+            format!("{}, {}", local(arg >> 4)?, local(arg & 15)?)
+        }
+        (_, ArgKind::Local | ArgKind::Free) => local(arg)?,
+        (_, ArgKind::Compare) => match version {
+            PythonVersion::V3_11 => lookup(&CMP_OP, arg)?,
+            PythonVersion::V3_12 => lookup(&CMP_OP, arg >> 4)?,
+            _ if arg & 16 != 0 => format!("bool({})", lookup(&CMP_OP, arg >> 5)?),
+            _ => lookup(&CMP_OP, arg >> 5)?,
+        },
+        (ConvertValue, _) => lookup(&CONVERSIONS, arg)?,
+        (FormatValue, _) => {
+            let conversion = lookup(&CONVERSIONS, arg & 3)?;
+            match (conversion.is_empty(), arg & 4 != 0) {
+                (_, false) => conversion,
+                (true, true) => "with format".into(),
+                (false, true) => format!("{conversion}, with format"),
+            }
+        }
+        (MakeFunction | SetFunctionAttribute, _) => FUNCTION_ATTR_FLAGS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| arg & (1 << i) != 0)
+            .map(|(_, flag)| *flag)
+            .collect::<Vec<_>>()
+            .join(", "),
+        (BinaryOp, _) => lookup(&NB_OPS, arg)?,
+        (CallIntrinsic1, _) => lookup(&INTRINSIC_1_DESCS, arg)?,
+        (CallIntrinsic2, _) => lookup(&INTRINSIC_2_DESCS, arg)?,
+        (LoadCommonConstant, _) => lookup(&COMMON_CONSTANTS, arg)?,
+        (LoadSpecial, _) => lookup(&SPECIAL_METHOD_NAMES, arg)?,
+        (IsOp, _) if version >= PythonVersion::V3_14 => {
+            if arg == 0 { "is" } else { "is not" }.into()
+        }
+        (ContainsOp, _) if version >= PythonVersion::V3_14 => {
+            if arg == 0 { "in" } else { "not in" }.into()
+        }
+        _ => String::new(),
+    })
+}
+
+/// The address ranges of the bytecode along with their line numbers, merging
+/// adjacent ranges on the same line, like `co_lines`. See `locations.md` in
+/// CPython for the format. A malformed table is read up to the first entry
+/// that is cut short or whose numbers don't fit
+pub(crate) fn co_lines(line_table: &[u8], first_line_no: i32) -> Vec<(usize, usize, Option<i32>)> {
+    fn varint(bytes: &mut impl Iterator<Item = u8>) -> Option<u32> {
+        let mut b = bytes.next()?;
+        let mut val = (b & 63) as u64;
+        let mut shift = 0;
+        while b & 64 != 0 {
+            b = bytes.next()?;
+            shift += 6;
+            if shift >= 32 {
+                return None;
+            }
+            val |= ((b & 63) as u64) << shift;
+        }
+        u32::try_from(val).ok()
+    }
+    fn svarint(bytes: &mut impl Iterator<Item = u8>) -> Option<i32> {
+        let val = varint(bytes)?;
+        match val & 1 {
+            0 => Some((val >> 1) as i32),
+            _ => Some(-((val >> 1) as i32)),
+        }
+    }
+
+    let mut ranges: Vec<(usize, usize, Option<i32>)> = Vec::new();
+    let mut bytes = line_table.iter().copied();
+    let mut line = first_line_no;
+    let mut start = 0;
+    while let Some(first) = bytes.next() {
+        let code = (first >> 3) & 15;
+        let end = start + 2 * ((first & 7) as usize + 1);
+        let range_line = match code {
+            // No location
+            15 => None,
+            // Long form, a line delta followed by the end line delta and
+            // columns, which we don't care about
+            14 => {
+                let Some(next) = svarint(&mut bytes).and_then(|delta| line.checked_add(delta))
+                else {
+                    break;
+                };
+                if (0..3).any(|_| varint(&mut bytes).is_none()) {
+                    break;
+                }
+                line = next;
+                Some(line)
+            }
+            // No column
+            13 => {
+                let Some(next) = svarint(&mut bytes).and_then(|delta| line.checked_add(delta))
+                else {
+                    break;
+                };
+                line = next;
+                Some(line)
+            }
+            // One line form, the line delta is in the code, followed by the
+            // start and end column
+            10..=12 => {
+                let Some(next) = line.checked_add(code as i32 - 10) else {
+                    break;
+                };
+                line = next;
+                bytes.nth(1);
+                Some(line)
+            }
+            // Short form, same line, one byte of column
+            _ => {
+                bytes.next();
+                Some(line)
+            }
+        };
+        match ranges.last_mut() {
+            Some((_, last_end, last_line)) if *last_line == range_line => *last_end = end,
+            _ => ranges.push((start, end, range_line)),
+        }
+        start = end;
+    }
+    ranges
+}
+
+/// Like `dis.findlinestarts`, the offsets at which the line number changes and
+/// the new line numbers. Before 3.13 the instructions without a line number are
+/// ignored, since then they are included with no line number
+fn find_line_starts(
+    version: PythonVersion,
+    lines: &[(usize, usize, Option<i32>)],
+) -> HashMap<usize, Option<i32>> {
+    let mut starts = HashMap::new();
+    let mut last_line = None;
+    for (start, _, line) in lines {
+        if version >= PythonVersion::V3_13 {
+            starts.insert(*start, *line);
+        } else if line.is_some() && *line != last_line {
+            starts.insert(*start, *line);
+            last_line = *line;
+        }
+    }
+    starts
+}
+
+fn lineno_width(version: PythonVersion, line_starts: &HashMap<usize, Option<i32>>) -> usize {
+    if version >= PythonVersion::V3_13 {
+        // `_get_lineno_width` filters out falsy line numbers, i.e. 0 as well
+        let Some(max) = line_starts.values().flatten().filter(|l| **l != 0).max() else {
+            return 0;
+        };
+        let width = max.to_string().len().max(3);
+        if width < NO_LINENO.len() && line_starts.values().any(Option::is_none) {
+            NO_LINENO.len()
+        } else {
+            width
+        }
+    } else {
+        match line_starts.values().flatten().max() {
+            None => 0,
+            Some(max) if *max >= 1000 => max.to_string().len(),
+            Some(_) => 3,
+        }
+    }
+}
+
+fn tuple<'r>(region: &'r PyObjectRegion, idx: PyObjectIndex) -> &'r [PyObjectIndex] {
+    match &region[idx] {
+        PyObject::Tuple(items) => items,
+        _ => unreachable!("CodeObjects should be proof that their tuple fields are tuples"),
+    }
+}
+
+fn string<'r>(region: &'r PyObjectRegion, idx: PyObjectIndex) -> &'r str {
+    match &region[idx] {
+        PyObject::String(s) => s,
+        _ => unreachable!("CodeObjects should be proof that their names are strings"),
+    }
+}
+
+fn bytes<'r>(region: &'r PyObjectRegion, idx: PyObjectIndex) -> &'r [u8] {
+    match &region[idx] {
+        PyObject::Bytes(b) => b,
+        _ => unreachable!("CodeObjects should be proof that their bytes fields are bytes"),
+    }
+}
+
+fn code_repr(idx: PyObjectIndex, code: &CodeObjectConstructor, region: &PyObjectRegion) -> String {
+    format!(
+        "<code object {} at {:#x}, file \"{}\", line {}>",
+        string(region, code.name),
+        idx.0,
+        string(region, code.filename),
+        code.first_line_no
+    )
+}
+
+/// Python's `repr` of a constant
+fn object_repr(idx: PyObjectIndex, region: &PyObjectRegion) -> String {
+    let items_repr = |items: &[PyObjectIndex]| {
+        items
+            .iter()
+            .map(|item| object_repr(*item, region))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match &region[idx] {
+        PyObject::None => "None".into(),
+        PyObject::Bool(true) => "True".into(),
+        PyObject::Bool(false) => "False".into(),
+        PyObject::StopIter => "<class 'StopIteration'>".into(),
+        PyObject::Ellipsis => "Ellipsis".into(),
+        PyObject::SmallInt(n) => n.to_string(),
+        PyObject::LargeInt(digits) => large_int_repr(digits),
+        PyObject::Float(f) => float_repr(*f),
+        PyObject::Complex(re, im) => complex_repr(*re, *im),
+        PyObject::Bytes(b) => bytes_repr(b),
+        PyObject::String(s) => str_repr(s),
+        PyObject::Tuple(items) if items.len() == 1 => format!("({},)", items_repr(items)),
+        PyObject::Tuple(items) => format!("({})", items_repr(items)),
+        PyObject::FrozenSet(items) if items.is_empty() => "frozenset()".into(),
+        PyObject::FrozenSet(items) => format!("frozenset({{{}}})", items_repr(items)),
+        PyObject::Code(code) => code_repr(idx, code, region),
+        PyObject::Null | PyObject::List(_) | PyObject::Dict(_) | PyObject::Set(_) => {
+            unreachable!("CodeObjects should be proof that their constants are immutable")
+        }
+    }
+}
+
+/// The digits of a large int are 15 bit, little endian and stored in 16 bits
+fn large_int_repr(digits: &[u8]) -> String {
+    // Repeatedly divide by 10^4, collecting the remainders, most significant
+    // digit first
+    let mut digits: Vec<u32> = digits
+        .chunks(2)
+        .rev()
+        .map(|d| u16::from_le_bytes([d[0], *d.get(1).unwrap_or(&0)]) as u32)
+        .collect();
+    let mut chunks = Vec::new();
+    while digits.iter().any(|d| *d != 0) {
+        let mut remainder = 0;
+        for digit in digits.iter_mut() {
+            let value = (remainder << 15) | *digit;
+            *digit = value / 10000;
+            remainder = value % 10000;
+        }
+        chunks.push(remainder);
+    }
+    match chunks.split_last() {
+        None => "0".into(),
+        Some((most_significant, rest)) => {
+            let mut out = most_significant.to_string();
+            for chunk in rest.iter().rev() {
+                out.push_str(&format!("{chunk:04}"));
+            }
+            out
+        }
+    }
+}
+
+/// Rust's `Debug` for floats uses the same shortest representation and
+/// switches to scientific notation at the same points as Python's `repr`, but
+/// Python always gives the exponent a sign and at least two digits
+fn float_repr(f: f64) -> String {
+    if f.is_nan() {
+        return "nan".into();
+    }
+    let repr = format!("{f:?}");
+    match repr.split_once('e') {
+        Some((mantissa, exponent)) => {
+            let (sign, digits) = match exponent.strip_prefix('-') {
+                Some(digits) => ('-', digits),
+                None => ('+', exponent),
+            };
+            format!("{mantissa}e{sign}{digits:0>2}")
+        }
+        None => repr,
+    }
+}
+
+fn complex_repr(re: f64, im: f64) -> String {
+    // Unlike floats, the parts of a complex number don't get a `.0`
+    let part = |f: f64| {
+        let repr = float_repr(f);
+        match repr.strip_suffix(".0") {
+            Some(integral) => integral.to_string(),
+            None => repr,
+        }
+    };
+    if re == 0.0 && re.is_sign_positive() {
+        format!("{}j", part(im))
+    } else {
+        let sign = if im.is_sign_negative() && !im.is_nan() {
+            ""
+        } else {
+            "+"
+        };
+        format!("({}{sign}{}j)", part(re), part(im))
+    }
+}
+
+fn str_repr(s: &str) -> String {
+    let quote = if s.contains('\'') && !s.contains('"') {
+        '"'
+    } else {
+        '\''
+    };
+    let mut out = String::from(quote);
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' ' => out.push(' '),
+            // This is an approximation of `str.isprintable`
+            c if c.is_control() || c.is_whitespace() => match c as u32 {
+                n @ ..0x100 => out.push_str(&format!("\\x{n:02x}")),
+                n @ ..0x10000 => out.push_str(&format!("\\u{n:04x}")),
+                n => out.push_str(&format!("\\U{n:08x}")),
+            },
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+    out
+}
+
+fn bytes_repr(b: &[u8]) -> String {
+    let quote = if b.contains(&b'\'') && !b.contains(&b'"') {
+        b'"'
+    } else {
+        b'\''
+    };
+    let mut out = format!("b{}", quote as char);
+    for byte in b {
+        match byte {
+            b'\\' => out.push_str("\\\\"),
+            b'\t' => out.push_str("\\t"),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b if *b == quote => {
+                out.push('\\');
+                out.push(*b as char);
+            }
+            b' '..=b'~' => out.push(*byte as char),
+            b => out.push_str(&format!("\\x{b:02x}")),
+        }
+    }
+    out.push(quote as char);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unmarshal::Unmarshaller;
+    use pretty_assertions::assert_eq;
+
+    /// Replace the addresses in code object reprs, since they can't match
+    fn strip_addresses(dis: &str) -> String {
+        let mut out = String::new();
+        let mut rest = dis;
+        while let Some(i) = rest.find(" at 0x") {
+            out.push_str(&rest[..i + 6]);
+            rest = rest[i + 6..].trim_start_matches(|c: char| c.is_ascii_hexdigit());
+        }
+        out.push_str(rest);
+        out
+    }
+
+    #[test]
+    /// Test that disassembling `examples/dis/sample.py` matches `dis.dis`
+    fn disassemble_matches_cpython() {
+        let expected = fixtures!("dis", "sample", "txt", include_str);
+        for ((version, marshalled), (_, expected)) in
+            fixtures!("dis", "sample").into_iter().zip(expected)
+        {
+            let region = Unmarshaller::loads(marshalled).unwrap();
+            let Some(PyObject::Code(code)) = region.first() else {
+                panic!("Expected a code object")
+            };
+            let code = code.construct(&region).unwrap();
+            let dis = disassemble(version, &code, &region).unwrap();
+            assert_eq!(
+                strip_addresses(&dis),
+                strip_addresses(expected),
+                "{version}"
+            );
+        }
+    }
+
+    #[test]
+    fn disassemble_unsupported_version() {
+        let region =
+            Unmarshaller::loads(include_bytes!("../examples/dis/sample.313.marshal")).unwrap();
+        let Some(PyObject::Code(code)) = region.first() else {
+            panic!("Expected a code object")
+        };
+        let code = code.construct(&region).unwrap();
+        assert_eq!(
+            disassemble(PythonVersion::V3_10, &code, &region),
+            Err(DisassembleError::UnsupportedVersion(PythonVersion::V3_10))
+        );
+    }
+
+    #[test]
+    /// Test that a line table is read up to an entry whose varint has too many
+    /// continuation bytes, or whose line overflows
+    fn co_lines_malformed() {
+        // A line one after the first, then the same with too long a varint
+        let mut table = vec![0xe8, 0x02, 0xe8];
+        table.extend([0x7f; 6]);
+        table.push(0x3f);
+        assert_eq!(co_lines(&table, 1), [(0, 2, Some(2))]);
+
+        // One line form that goes past the last line there can be
+        assert_eq!(co_lines(&[0xd8, 0, 0], i32::MAX), []);
+        assert_eq!(co_lines(&[0xe8, 0x02], i32::MAX), []);
+    }
+
+    #[test]
+    fn reprs() {
+        assert_eq!(float_repr(1.5e20), "1.5e+20");
+        assert_eq!(float_repr(-1e-7), "-1e-07");
+        assert_eq!(float_repr(0.0001), "0.0001");
+        assert_eq!(float_repr(2.0), "2.0");
+        assert_eq!(complex_repr(0.0, 3.0), "3j");
+        assert_eq!(complex_repr(1.5, -2.0), "(1.5-2j)");
+        assert_eq!(str_repr("it's"), "\"it's\"");
+        assert_eq!(str_repr("a'\"\n\u{1}"), "'a\\'\"\\n\\x01'");
+        assert_eq!(bytes_repr(b"\x00raw'\""), "b'\\x00raw\\'\"'");
+        assert_eq!(
+            large_int_repr(&[0, 0, 0, 0, 4, 0]),
+            (1u64 << 32).to_string()
+        );
+    }
+}
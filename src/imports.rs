@@ -0,0 +1,218 @@
+//! Finding the imports in a module, for auditing which modules it depends on.
+//! This works directly on the bytecode rather than through abstract
+//! interpretation, so that it works on code that can't be evaluated yet
+
+use crate::{
+    dis::co_lines,
+    objects::{
+        CodeObjectConstructionError, CodeObjectConstructor, PyObject, PyObjectIndex, PyObjectRegion,
+    },
+    opcode::{DecodeError, Opcode, decode},
+    version::PythonVersion,
+};
+
+/// An `import` statement (or `__import__` in general) found in a module
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Import {
+    /// The module as written, without any leading dots, e.g. `os.path`, or
+    /// empty for `from . import x`
+    pub module: String,
+    /// The names imported from the module, which are empty for `import x` and
+    /// `["*"]` for star imports
+    pub fromlist: Vec<String>,
+    /// The number of leading dots of a relative import, or 0 if it's absolute
+    pub level: u32,
+    /// The line of the import, if it has one
+    pub line: Option<i32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ModuleImportsError<'a> {
+    /// Only code objects from 3.11 onwards can be unmarshalled
+    UnsupportedVersion(PythonVersion),
+    /// The root of the region isn't a code object
+    ExpectedModuleCode(Option<&'a PyObject<'a>>),
+    InvalidCode(CodeObjectConstructionError<'a>),
+    Decode(DecodeError),
+    /// The `IMPORT_NAME` at this offset doesn't have a constant level and
+    /// fromlist loaded just before it
+    UnexpectedImportArgs {
+        offset: usize,
+    },
+}
+
+/// Find every import in the module at the root of the region, including in
+/// nested functions and classes, in the order of the code objects they are in
+pub fn module_imports<'a>(
+    version: PythonVersion,
+    region: &'a PyObjectRegion<'a>,
+) -> Result<Vec<Import>, ModuleImportsError<'a>> {
+    if version < PythonVersion::V3_11 {
+        return Err(ModuleImportsError::UnsupportedVersion(version));
+    }
+    let root = match region.first() {
+        Some(PyObject::Code(code)) => code,
+        found => return Err(ModuleImportsError::ExpectedModuleCode(found)),
+    };
+    root.construct(region)
+        .map_err(ModuleImportsError::InvalidCode)?;
+    let mut imports = Vec::new();
+    code_imports(version, root, region, &mut imports)?;
+    Ok(imports)
+}
+
+/// A constant that is loaded by an instruction, which are the arguments of
+/// `IMPORT_NAME`
+#[derive(Clone, Copy)]
+enum Loaded {
+    SmallInt(u32),
+    Const(PyObjectIndex),
+    Other,
+}
+
+fn code_imports<'a>(
+    version: PythonVersion,
+    code: &CodeObjectConstructor,
+    region: &'a PyObjectRegion<'a>,
+    imports: &mut Vec<Import>,
+) -> Result<(), ModuleImportsError<'a>> {
+    // Everything is checked to have the right type by construct
+    let (Some(PyObject::Bytes(bytecode)), Some(PyObject::Tuple(consts))) =
+        (region.get(code.code), region.get(code.consts))
+    else {
+        unreachable!()
+    };
+    let Some(PyObject::Tuple(names)) = region.get(code.names) else {
+        unreachable!()
+    };
+    let Some(PyObject::Bytes(line_table)) = region.get(code.line_table) else {
+        unreachable!()
+    };
+    let lines = co_lines(line_table, code.first_line_no);
+
+    let mut loaded = [Loaded::Other; 2];
+    for raw in decode(version, bytecode) {
+        let raw = raw.map_err(ModuleImportsError::Decode)?;
+        let this = match raw.opcode {
+            Opcode::LoadSmallInt => Loaded::SmallInt(raw.arg),
+            Opcode::LoadConst => match consts.get(raw.arg as usize) {
+                Some(idx) => Loaded::Const(*idx),
+                None => Loaded::Other,
+            },
+            Opcode::ImportName => {
+                let args = import_args(loaded, region);
+                let (Some((level, fromlist)), Some(module)) =
+                    (args, string(region, names.get(raw.arg as usize)))
+                else {
+                    return Err(ModuleImportsError::UnexpectedImportArgs { offset: raw.offset });
+                };
+                let line = lines
+                    .iter()
+                    .find(|(start, end, _)| (*start..*end).contains(&raw.offset))
+                    .and_then(|(_, _, line)| *line);
+                imports.push(Import {
+                    module,
+                    fromlist,
+                    level,
+                    line,
+                });
+                Loaded::Other
+            }
+            _ => Loaded::Other,
+        };
+        loaded = [loaded[1], this];
+    }
+
+    for idx in consts.iter() {
+        if let Some(PyObject::Code(nested)) = region.get(*idx) {
+            code_imports(version, nested, region, imports)?;
+        }
+    }
+    Ok(())
+}
+
+fn string(region: &PyObjectRegion, idx: Option<&PyObjectIndex>) -> Option<String> {
+    match idx.and_then(|idx| region.get(*idx)) {
+        Some(PyObject::String(s)) => Some(s.to_string()),
+        _ => None,
+    }
+}
+
+/// The level and fromlist of an import, from the two constants loaded before
+/// `IMPORT_NAME`
+fn import_args(loaded: [Loaded; 2], region: &PyObjectRegion) -> Option<(u32, Vec<String>)> {
+    let level = match loaded[0] {
+        Loaded::SmallInt(level) => level,
+        Loaded::Const(idx) => match region.get(idx) {
+            Some(PyObject::SmallInt(level)) => u32::try_from(*level).ok()?,
+            _ => return None,
+        },
+        Loaded::Other => return None,
+    };
+    let Loaded::Const(fromlist) = loaded[1] else {
+        return None;
+    };
+    let fromlist = match region.get(fromlist) {
+        Some(PyObject::None) => Vec::new(),
+        Some(PyObject::Tuple(items)) => items
+            .iter()
+            .map(|item| string(region, Some(item)))
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    Some((level, fromlist))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unmarshal::Unmarshaller;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    /// Test examples/dis/sample.py, which has absolute, star and relative
+    /// imports in the module and a relative import in a function
+    fn module_imports_sample() {
+        for (version, marshalled) in fixtures!("dis", "sample") {
+            let region = Unmarshaller::loads(marshalled).unwrap();
+            assert_eq!(
+                module_imports(version, &region),
+                Ok(vec![
+                    Import {
+                        module: "os".to_string(),
+                        fromlist: vec![],
+                        level: 0,
+                        line: Some(1),
+                    },
+                    Import {
+                        module: "collections".to_string(),
+                        fromlist: vec!["namedtuple".to_string()],
+                        level: 0,
+                        line: Some(2),
+                    },
+                    Import {
+                        module: "os.path".to_string(),
+                        fromlist: vec!["*".to_string()],
+                        level: 0,
+                        line: Some(48),
+                    },
+                    Import {
+                        module: "".to_string(),
+                        fromlist: vec!["sibling".to_string()],
+                        level: 2,
+                        line: Some(49),
+                    },
+                    // Imports in nested code objects come after the module's
+                    Import {
+                        module: "util".to_string(),
+                        fromlist: vec!["helper".to_string()],
+                        level: 1,
+                        line: Some(53),
+                    },
+                ]),
+                "{version}"
+            );
+        }
+    }
+}
@@ -1,5 +1,63 @@
+/// The 3.11, 3.12 and 3.13 versions of a test fixture compiled from
+/// `examples/<dir>/<name>.py`, as `(PythonVersion, &[u8])` pairs of the
+/// marshalled module. The dir is `eval` unless it's given, and other files
+/// compiled from it can be included with their extension and `include_str`
+#[cfg(test)]
+macro_rules! fixtures {
+    ($name:literal) => {
+        fixtures!("eval", $name)
+    };
+    ($dir:literal, $name:literal) => {
+        fixtures!($dir, $name, "marshal", include_bytes)
+    };
+    ($dir:literal, $name:literal, $extension:literal, $include:ident) => {
+        [
+            (
+                $crate::version::PythonVersion::V3_11,
+                &$include!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/examples/",
+                    $dir,
+                    "/",
+                    $name,
+                    ".311.",
+                    $extension
+                ))[..],
+            ),
+            (
+                $crate::version::PythonVersion::V3_12,
+                &$include!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/examples/",
+                    $dir,
+                    "/",
+                    $name,
+                    ".312.",
+                    $extension
+                ))[..],
+            ),
+            (
+                $crate::version::PythonVersion::V3_13,
+                &$include!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/examples/",
+                    $dir,
+                    "/",
+                    $name,
+                    ".313.",
+                    $extension
+                ))[..],
+            ),
+        ]
+    };
+}
+
 pub mod objects;
 use objects::*;
 pub mod abstract_interpretation;
+pub mod dis;
+pub mod imports;
+pub mod opcode;
 pub mod stack_ir;
 pub mod unmarshal;
+pub mod version;
@@ -1,4 +1,10 @@
-use std::ops::Index;
+use std::{
+    borrow::Cow,
+    ops::{
+        ControlFlow::{self, Break, Continue},
+        Index,
+    },
+};
 
 use crate::{
     abstract_interpretation::{Place, eval::EvalCtx},
@@ -6,20 +12,27 @@ use crate::{
 };
 
 #[derive(Debug, PartialEq)]
-pub struct PyObjectRegion(pub(crate) Vec<PyObject>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PyObjectRegion<'a>(pub(crate) Vec<PyObject<'a>>);
 
-impl PyObjectRegion {
-    pub fn get(&self, index: PyObjectIndex) -> Option<&PyObject> {
+impl<'a> PyObjectRegion<'a> {
+    pub fn get(&self, index: PyObjectIndex) -> Option<&PyObject<'a>> {
         self.0.get(index.0)
     }
 
-    pub fn first(&self) -> Option<&PyObject> {
-        self.0.get(0)
+    pub fn first(&self) -> Option<&PyObject<'a>> {
+        self.0.first()
+    }
+
+    /// Copy any payloads that are borrowed from the unmarshalled input, so that
+    /// the region can outlive it
+    pub fn into_owned(self) -> PyObjectRegion<'static> {
+        PyObjectRegion(self.0.into_iter().map(PyObject::into_owned).collect())
     }
 }
 
-impl Index<PyObjectIndex> for PyObjectRegion {
-    type Output = PyObject;
+impl<'a> Index<PyObjectIndex> for PyObjectRegion<'a> {
+    type Output = PyObject<'a>;
 
     fn index(&self, index: PyObjectIndex) -> &Self::Output {
         &self.0[index.0]
@@ -27,21 +40,23 @@ impl Index<PyObjectIndex> for PyObjectRegion {
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PyObjectIndex(pub usize);
 
 #[derive(Debug, PartialEq)]
-pub enum PyObject {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PyObject<'a> {
     Null,
     None,
     Bool(bool),
     StopIter,
     Ellipsis,
     SmallInt(i64),
-    LargeInt(PyLargeInt),
+    LargeInt(PyLargeInt<'a>),
     Float(f64),
     Complex(f64, f64),
-    Bytes(Box<[u8]>),
-    String(Box<str>),
+    Bytes(Cow<'a, [u8]>),
+    String(Cow<'a, str>),
     Tuple(Box<[PyObjectIndex]>),
     List(Box<[PyObjectIndex]>),
     Dict(Box<[(PyObjectIndex, PyObjectIndex)]>),
@@ -49,19 +64,36 @@ pub enum PyObject {
     FrozenSet(Box<[PyObjectIndex]>),
     Code(CodeObjectConstructor),
 }
-type PyLargeInt = Box<[u8]>;
-
-impl PyObject {
-    fn is_bytes(&self) -> bool {
-        if let PyObject::Bytes(_) = self {
-            true
-        } else {
-            false
+type PyLargeInt<'a> = Cow<'a, [u8]>;
+
+impl PyObject<'_> {
+    /// Copy the payload of this object if it is borrowed from the unmarshalled
+    /// input
+    pub fn into_owned(self) -> PyObject<'static> {
+        match self {
+            PyObject::Null => PyObject::Null,
+            PyObject::None => PyObject::None,
+            PyObject::Bool(b) => PyObject::Bool(b),
+            PyObject::StopIter => PyObject::StopIter,
+            PyObject::Ellipsis => PyObject::Ellipsis,
+            PyObject::SmallInt(n) => PyObject::SmallInt(n),
+            PyObject::LargeInt(n) => PyObject::LargeInt(Cow::Owned(n.into_owned())),
+            PyObject::Float(f) => PyObject::Float(f),
+            PyObject::Complex(re, im) => PyObject::Complex(re, im),
+            PyObject::Bytes(b) => PyObject::Bytes(Cow::Owned(b.into_owned())),
+            PyObject::String(s) => PyObject::String(Cow::Owned(s.into_owned())),
+            PyObject::Tuple(items) => PyObject::Tuple(items),
+            PyObject::List(items) => PyObject::List(items),
+            PyObject::Dict(items) => PyObject::Dict(items),
+            PyObject::Set(items) => PyObject::Set(items),
+            PyObject::FrozenSet(items) => PyObject::FrozenSet(items),
+            PyObject::Code(code) => PyObject::Code(code),
         }
     }
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CodeObjectConstructor {
     pub(crate) arg_count: i32,
     pub(crate) pos_only_arg_count: i32,
@@ -89,7 +121,7 @@ pub struct CodeObjectConstructor {
 /// will not attempt an out of bound access, or pop from an empty stack, are not
 /// in scope
 #[derive(Debug, PartialEq)]
-pub struct CodeObject<'a>(&'a CodeObjectConstructor);
+pub struct CodeObject<'a>(pub(crate) &'a CodeObjectConstructor);
 impl<'a> CodeObject<'a> {
     /// Get the co_code field of this code object as a [`&[u8]`](slice)
     pub fn code(&'a self, region: &'a PyObjectRegion) -> &'a [u8] {
@@ -101,28 +133,64 @@ impl<'a> CodeObject<'a> {
         }
     }
 
+    pub fn exception_table(&'a self, region: &'a PyObjectRegion) -> &'a [u8] {
+        match region.get(self.0.exception_table) {
+            Some(PyObject::Bytes(b)) => b,
+            _ => unreachable!(
+                "Objects of type CodeObject should be proof that their exception table is a bytes object"
+            ),
+        }
+    }
+
     pub fn stack_size(&self) -> i32 {
         self.0.stack_size
     }
 
-    fn locals(&self, region: &'a PyObjectRegion) -> impl Iterator<Item = &str> {
+    /// The index of the constant at `index` in co_consts, if it is in bounds
+    pub(crate) fn constant(&self, index: u32, region: &PyObjectRegion) -> Option<PyObjectIndex> {
+        let Some(PyObject::Tuple(consts)) = region.get(self.0.consts) else {
+            unreachable!()
+        };
+        consts.get(index as usize).copied()
+    }
+
+    /// The locals, with their indices in the fast locals
+    fn locals(&self, region: &'a PyObjectRegion) -> impl Iterator<Item = (u32, &str)> {
         let Some(PyObject::Tuple(locals_plus_names)) = region.get(self.0.locals_plus_names) else {
             unreachable!()
         };
         locals_plus_names
             .iter()
             .zip(self.local_flags(region))
-            .filter_map(|(name, kind)| {
+            .enumerate()
+            .filter_map(|(i, (name, kind))| {
                 if !kind.is_local() {
                     return None;
                 }
                 let Some(PyObject::String(name)) = region.get(*name) else {
                     unreachable!();
                 };
-                Some(name.as_ref())
+                Some((i as u32, name.as_ref()))
             })
     }
 
+    /// The name of the variable at this index of the fast locals, which are
+    /// the locals, cell variables and free variables
+    pub fn local_name(&self, index: u32, region: &'a PyObjectRegion) -> Option<&'a str> {
+        let Some(PyObject::Tuple(locals_plus_names)) = region.get(self.0.locals_plus_names) else {
+            unreachable!()
+        };
+        match region.get(*locals_plus_names.get(index as usize)?) {
+            Some(PyObject::String(name)) => Some(name.as_ref()),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The kind of the variable at this index of the fast locals
+    pub fn local_kind(&self, index: u32, region: &'a PyObjectRegion) -> Option<LocalFlags> {
+        self.local_flags(region).nth(index as usize)
+    }
+
     fn local_flags(&self, region: &'a PyObjectRegion) -> impl Iterator<Item = LocalFlags> {
         let Some(PyObject::Bytes(locals_plus_kinds)) = region.get(self.0.locals_plus_kinds) else {
             unreachable!()
@@ -139,12 +207,20 @@ impl<'a> CodeObject<'a> {
                 let Some(PyObject::String(name)) = ctx.region.get(names[*i as usize]) else {
                     unreachable!()
                 };
-                for (i, local_name) in self.locals(ctx.region).enumerate() {
+                for (i, local_name) in self.locals(ctx.region) {
                     if local_name == name.as_ref() {
-                        return Place::Local(i as u32);
+                        return Place::Local(i);
                     }
                 }
-                return Place::Global(*i);
+                Place::Global(*i)
+            }
+            // Cells are loaded as locals to build the closure of a function
+            UnresolvedPlace::Local(i)
+                if self
+                    .local_kind(*i, ctx.region)
+                    .is_some_and(|kind| kind.is_cell() || kind.is_free()) =>
+            {
+                Place::Cell(*i)
             }
             place => Place::from_unresolved_unchecked(place),
         }
@@ -154,6 +230,7 @@ impl<'a> CodeObject<'a> {
 #[derive(Debug, PartialEq)]
 pub struct LocalFlags(u8);
 
+#[allow(dead_code)]
 impl LocalFlags {
     const ARG_POS: u8 = 0x02;
     const ARG_KW: u8 = 0x04;
@@ -161,9 +238,18 @@ impl LocalFlags {
     const HIDDEN: u8 = 0x10;
     const LOCAL: u8 = 0x20;
     const CELL: u8 = 0x40;
-    fn is_local(&self) -> bool {
+    const FREE: u8 = 0x80;
+    pub fn is_local(&self) -> bool {
         self.0 & Self::LOCAL != 0
     }
+    /// A variable of this code object that nested functions refer to
+    pub fn is_cell(&self) -> bool {
+        self.0 & Self::CELL != 0
+    }
+    /// A variable of an enclosing function that this one refers to
+    pub fn is_free(&self) -> bool {
+        self.0 & Self::FREE != 0
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -173,7 +259,7 @@ impl CodeObjectFlags {
     /// Returns true if this is a valid set of code object flags
     // TODO: Actually implement validation
     fn validate(_raw: i32) -> bool {
-        return true;
+        true
     }
 }
 
@@ -188,13 +274,13 @@ pub enum CodeObjectConstructionError<'a> {
     /// Expected the bytecode string to be bytes with an even length, actual is
     /// noted. Even length is expected because, since CPython 3.6, instructions
     /// are stored as pairs of bytes
-    ExpectedCodeEvenLenBytes(&'a PyObject),
+    ExpectedCodeEvenLenBytes(&'a PyObject<'a>),
     /// Expected the locals_plus_names field to be a tuple of strings, actual
     /// is noted (with the caveat that due to the way that sequences are
     /// represented, the type of any children will not be immediately apparent
-    ExpectedLocalsTupleOfStrings(&'a PyObject),
+    ExpectedLocalsTupleOfStrings(&'a PyObject<'a>),
     /// Expected the locals_plus_kinds to be bytes, actual is noted
-    ExpectedLocalsBytes(&'a PyObject),
+    ExpectedLocalsBytes(&'a PyObject<'a>),
     /// Expected locals_plus_names and locals_plus_kinds to have equal length,
     /// their actual lengths are noted
     LocalsSizeMismatch { names_len: usize, kinds_len: usize },
@@ -212,78 +298,160 @@ pub enum CodeObjectConstructionError<'a> {
     },
     /// Expected names to be a tuple, actual is noted, same caveats apply as to
     /// the equivalent for locals in terms of children
-    ExpectedNamesTupleOfStrings(&'a PyObject),
+    ExpectedNamesTupleOfStrings(&'a PyObject<'a>),
     /// Expected consts to be a tuple, actual is noted
-    ExpectedConstsTuple(&'a PyObject),
+    ExpectedConstsTuple(&'a PyObject<'a>),
     /// Expected filename to be a string, actual is noted
-    ExpectedFileNameString(&'a PyObject),
+    ExpectedFileNameString(&'a PyObject<'a>),
+    /// Expected name to be a string, actual is noted
+    ExpectedNameString(&'a PyObject<'a>),
     /// Expected qualified name to be a string, actual is noted
-    ExpectedQualifiedNameString(&'a PyObject),
-    /// Expected name to be a subtstring of the qualified name, actual is noted
-    // I am not entirely sure that this actually true of all names / qualnames
-    // that are created by CPython, but I can't imagine where it wouldn't be
-    // valid, and I'll relax the requirement if it comes up
-    ExpectedNameSubstringQualifiedName(&'a PyObject),
+    ExpectedQualifiedNameString(&'a PyObject<'a>),
+    /// Expected the qualified name to either be the name, or to be a dotted
+    /// path ending in the name (e.g. `f.<locals>.<lambda>` for `<lambda>`),
+    /// actual values are noted
+    QualifiedNameMismatch {
+        name: &'a str,
+        qualified_name: &'a str,
+    },
+    /// Expected every constant to be an immutable marshallable object (i.e.
+    /// not a list, dict, set or null), the path to the offending constant
+    /// (the index into consts, followed by indices into any tuples or
+    /// frozensets) and the actual object are noted
+    InvalidConst {
+        path: Box<[usize]>,
+        found: &'a PyObject<'a>,
+    },
+    /// A constant contains itself, the path to the second occurrence is noted
+    CyclicConst { path: Box<[usize]> },
+    /// A code object nested in the constants failed to construct, the path to
+    /// it and its error are noted
+    InConst {
+        path: Box<[usize]>,
+        error: Box<CodeObjectConstructionError<'a>>,
+    },
     /// Expected first line number to be greater than 0, actual is noted
     ExpectedLineNoGtZero(i32),
     /// Expected the line table to be bytes, actual is noted
-    ExpectedLineTableBytes(&'a PyObject),
+    ExpectedLineTableBytes(&'a PyObject<'a>),
     /// Expected the exception table to be bytes, actual is noted
-    ExpectedExceptionTableBytes(&'a PyObject),
+    ExpectedExceptionTableBytes(&'a PyObject<'a>),
     /// Expected stack size to be at least 0, actual is noted
     ExpectedStackSize(i32),
     /// Flags are invalid / inconsistent
     InvalidFlags(i32),
+    /// Expected the root of a module to be a code object, actual is noted
+    ExpectedModuleCode(&'a PyObject<'a>),
 }
 
 impl CodeObjectConstructor {
     /// Consume self and try to create a valid `CodeObject` that wraps this and
-    /// provides an interface for getting computed properties
+    /// provides an interface for getting computed properties. Any code objects
+    /// in the constants are validated as well
     pub fn construct<'a>(
         &'a self,
-        region: &'a PyObjectRegion,
+        region: &'a PyObjectRegion<'a>,
+    ) -> Result<CodeObject<'a>, CodeObjectConstructionError<'a>> {
+        self.construct_inner(region, &mut Vec::new())
+    }
+
+    fn construct_inner<'a>(
+        &'a self,
+        region: &'a PyObjectRegion<'a>,
+        ancestors: &mut Vec<PyObjectIndex>,
     ) -> Result<CodeObject<'a>, CodeObjectConstructionError<'a>> {
         use CodeObjectConstructionError as CE;
-        match region.get(self.code) {
-            None => return Err(CE::OutOfBoundsIndex(self.code)),
-            Some(PyObject::Bytes(b)) if b.len() % 2 == 0 => {}
-            Some(other_obj) => return Err(CE::ExpectedCodeEvenLenBytes(other_obj)),
+        if let Some(error) = self.check(region).into_iter().next() {
+            return Err(error);
         }
 
-        let (locals_plus_names_obj, locals_plus_names) = match region.get(self.locals_plus_names) {
-            None => return Err(CE::OutOfBoundsIndex(self.locals_plus_names)),
-            Some(b @ PyObject::Tuple(inner)) => (b, inner),
-            Some(obj) => return Err(CE::ExpectedLocalsTupleOfStrings(obj)),
+        let Some(PyObject::Tuple(consts)) = region.get(self.consts) else {
+            unreachable!("check should have rejected a non tuple consts field")
         };
-        if !locals_plus_names.iter().all(|idx| {
-            if let Some(PyObject::String(_)) = region.get(*idx) {
-                true
-            } else {
-                false
+        let mut path = Vec::new();
+        for (i, idx) in consts.iter().enumerate() {
+            path.push(i);
+            let walk = walk_const(
+                *idx,
+                region,
+                &mut path,
+                ancestors,
+                &mut |visit, path, ancestors| match visit {
+                    ConstVisit::Code(code) => match code.construct_inner(region, ancestors) {
+                        Ok(_) => Continue(()),
+                        Err(error) => Break(CE::InConst {
+                            path: path.into(),
+                            error: Box::new(error),
+                        }),
+                    },
+                    ConstVisit::Invalid(error) => Break(error),
+                },
+            );
+            if let Break(error) = walk {
+                return Err(error);
             }
-        }) {
-            return Err(CE::ExpectedLocalsTupleOfStrings(locals_plus_names_obj));
+            path.pop();
         }
-        let locals_plus_kinds = match region.get(self.locals_plus_kinds) {
-            None => return Err(CE::OutOfBoundsIndex(self.locals_plus_kinds)),
-            Some(PyObject::Bytes(b)) => b,
-            Some(obj) => return Err(CE::ExpectedLocalsBytes(obj)),
+
+        Ok(CodeObject(self))
+    }
+
+    /// Run every check on this code object that doesn't involve looking at
+    /// other code objects, returning all of the errors found (in the order that
+    /// [`construct`](Self::construct) would find them). Checks that depend on a
+    /// field that is already known to be invalid are skipped
+    fn check<'a>(&'a self, region: &'a PyObjectRegion<'a>) -> Vec<CodeObjectConstructionError<'a>> {
+        use CodeObjectConstructionError as CE;
+        let mut errors = Vec::new();
+
+        match region.get(self.code) {
+            None => errors.push(CE::OutOfBoundsIndex(self.code)),
+            Some(PyObject::Bytes(b)) if b.len() % 2 == 0 => {}
+            Some(other_obj) => errors.push(CE::ExpectedCodeEvenLenBytes(other_obj)),
+        }
+
+        let locals_plus_names = match region.get(self.locals_plus_names) {
+            None => {
+                errors.push(CE::OutOfBoundsIndex(self.locals_plus_names));
+                None
+            }
+            Some(b @ PyObject::Tuple(inner)) => {
+                if !inner
+                    .iter()
+                    .all(|idx| matches!(region.get(*idx), Some(PyObject::String(_))))
+                {
+                    errors.push(CE::ExpectedLocalsTupleOfStrings(b));
+                }
+                Some(inner)
+            }
+            Some(obj) => {
+                errors.push(CE::ExpectedLocalsTupleOfStrings(obj));
+                None
+            }
         };
-        if locals_plus_kinds.len() != locals_plus_names.len() {
-            return Err(CE::LocalsSizeMismatch {
-                names_len: locals_plus_names.len(),
-                kinds_len: locals_plus_kinds.len(),
-            });
+        match region.get(self.locals_plus_kinds) {
+            None => errors.push(CE::OutOfBoundsIndex(self.locals_plus_kinds)),
+            Some(PyObject::Bytes(locals_plus_kinds)) => match locals_plus_names {
+                Some(locals_plus_names) if locals_plus_kinds.len() != locals_plus_names.len() => {
+                    errors.push(CE::LocalsSizeMismatch {
+                        names_len: locals_plus_names.len(),
+                        kinds_len: locals_plus_kinds.len(),
+                    })
+                }
+                _ => (),
+            },
+            Some(obj) => errors.push(CE::ExpectedLocalsBytes(obj)),
         }
 
         // TODO: Actually count the number of args types and verify that matches the claimed
-        if self.arg_count < 0
-            || self.pos_only_arg_count < 0
-            || self.kw_only_arg_count < 0
-            || self.arg_count as usize > locals_plus_names.len()
-            || self.pos_only_arg_count + self.kw_only_arg_count > self.arg_count
+        if let Some(locals_plus_names) = locals_plus_names
+            && (self.arg_count < 0
+                || self.pos_only_arg_count < 0
+                || self.kw_only_arg_count < 0
+                || self.pos_only_arg_count > self.arg_count
+                || (self.arg_count + self.kw_only_arg_count) as usize > locals_plus_names.len())
         {
-            return Err(CE::ArgCountMismatch {
+            errors.push(CE::ArgCountMismatch {
                 arg_count: self.arg_count,
                 pos_only_arg_count: self.pos_only_arg_count,
                 kw_only_arg_count: self.kw_only_arg_count,
@@ -291,73 +459,510 @@ impl CodeObjectConstructor {
             });
         }
 
-        let (names_obj, names) = match region.get(self.names) {
-            None => return Err(CE::OutOfBoundsIndex(self.names)),
-            Some(b @ PyObject::Tuple(inner)) => (b, inner),
-            Some(obj) => return Err(CE::ExpectedNamesTupleOfStrings(obj)),
-        };
-        if !names.iter().all(|idx| {
-            if let Some(PyObject::String(_)) = region.get(*idx) {
-                true
-            } else {
-                false
+        match region.get(self.names) {
+            None => errors.push(CE::OutOfBoundsIndex(self.names)),
+            Some(b @ PyObject::Tuple(names)) => {
+                if !names
+                    .iter()
+                    .all(|idx| matches!(region.get(*idx), Some(PyObject::String(_))))
+                {
+                    errors.push(CE::ExpectedNamesTupleOfStrings(b));
+                }
             }
-        }) {
-            return Err(CE::ExpectedNamesTupleOfStrings(names_obj));
+            Some(obj) => errors.push(CE::ExpectedNamesTupleOfStrings(obj)),
         }
 
         match region.get(self.consts) {
-            None => return Err(CE::OutOfBoundsIndex(self.consts)),
+            None => errors.push(CE::OutOfBoundsIndex(self.consts)),
             Some(PyObject::Tuple(_)) => (),
-            Some(found) => return Err(CE::ExpectedConstsTuple(found)),
+            Some(found) => errors.push(CE::ExpectedConstsTuple(found)),
         }
 
         match region.get(self.filename) {
-            None => return Err(CE::OutOfBoundsIndex(self.filename)),
+            None => errors.push(CE::OutOfBoundsIndex(self.filename)),
             Some(PyObject::String(_)) => (),
-            Some(found) => return Err(CE::ExpectedFileNameString(found)),
+            Some(found) => errors.push(CE::ExpectedFileNameString(found)),
         }
 
+        let name = match region.get(self.name) {
+            None => {
+                errors.push(CE::OutOfBoundsIndex(self.name));
+                None
+            }
+            Some(PyObject::String(s)) => Some(s.as_ref()),
+            Some(found) => {
+                errors.push(CE::ExpectedNameString(found));
+                None
+            }
+        };
+
         let qualified_name = match region.get(self.qualified_name) {
-            None => return Err(CE::OutOfBoundsIndex(self.name)),
-            Some(PyObject::String(s)) => s.as_ref(),
-            Some(found) => return Err(CE::ExpectedQualifiedNameString(found)),
+            None => {
+                errors.push(CE::OutOfBoundsIndex(self.qualified_name));
+                None
+            }
+            Some(PyObject::String(s)) => Some(s.as_ref()),
+            Some(found) => {
+                errors.push(CE::ExpectedQualifiedNameString(found));
+                None
+            }
         };
 
-        match region.get(self.qualified_name) {
-            None => return Err(CE::OutOfBoundsIndex(self.qualified_name)),
-            Some(PyObject::String(name)) if qualified_name.find(name.as_ref()).is_some() => (),
-            Some(found) => return Err(CE::ExpectedNameSubstringQualifiedName(found)),
+        if let (Some(name), Some(qualified_name)) = (name, qualified_name)
+            && !qualified_name_matches(name, qualified_name)
+        {
+            errors.push(CE::QualifiedNameMismatch {
+                name,
+                qualified_name,
+            });
         }
 
         if self.first_line_no < 1 {
-            return Err(CE::ExpectedLineNoGtZero(self.first_line_no));
+            errors.push(CE::ExpectedLineNoGtZero(self.first_line_no));
         }
 
         // TODO: Validate that this is a valid line table, and that it covers
         // the entire bytecode object
         match region.get(self.line_table) {
-            None => return Err(CE::OutOfBoundsIndex(self.line_table)),
+            None => errors.push(CE::OutOfBoundsIndex(self.line_table)),
             Some(PyObject::Bytes(_)) => (),
-            Some(found) => return Err(CE::ExpectedLineTableBytes(found)),
+            Some(found) => errors.push(CE::ExpectedLineTableBytes(found)),
         }
 
         // TODO: Validate that this is a valid exception table, and that it
         // covers the entire bytecode object
         match region.get(self.exception_table) {
-            None => return Err(CE::OutOfBoundsIndex(self.exception_table)),
+            None => errors.push(CE::OutOfBoundsIndex(self.exception_table)),
             Some(PyObject::Bytes(_)) => (),
-            Some(found) => return Err(CE::ExpectedExceptionTableBytes(found)),
+            Some(found) => errors.push(CE::ExpectedExceptionTableBytes(found)),
         }
 
         if self.stack_size < 0 {
-            return Err(CE::ExpectedStackSize(self.stack_size));
+            errors.push(CE::ExpectedStackSize(self.stack_size));
         }
 
         if !CodeObjectFlags::validate(self.flags) {
-            return Err(CE::InvalidFlags(self.flags));
+            errors.push(CE::InvalidFlags(self.flags));
         }
 
-        Ok(CodeObject(self))
+        errors
+    }
+
+    /// The qualified name of this code object, falling back to its name if the
+    /// qualified name isn't a string, for use in error reporting
+    fn display_name<'a>(&'a self, region: &'a PyObjectRegion<'a>) -> &'a str {
+        match (region.get(self.qualified_name), region.get(self.name)) {
+            (Some(PyObject::String(name)), _) | (_, Some(PyObject::String(name))) => name,
+            _ => "<unknown>",
+        }
+    }
+}
+
+/// An error found by [`validate_module`], along with where it was found
+#[derive(Debug, PartialEq)]
+pub struct ModuleValidationError<'a> {
+    /// The qualified names of the code objects leading to the one with the
+    /// error, starting at the module, e.g. `["<module>", "f", "f.<locals>.g"]`.
+    /// A code object without a string qualified name is represented by its name,
+    /// or by `<unknown>` if it has neither
+    pub qualname_path: Box<[&'a str]>,
+    /// The error itself. Errors in nested code objects are reported against
+    /// the nested code object, so this is never [`InConst`](CodeObjectConstructionError::InConst)
+    pub error: CodeObjectConstructionError<'a>,
+}
+
+/// Validate every code object in a module, starting from the root of the
+/// region. Unlike [`CodeObjectConstructor::construct`], this doesn't stop at
+/// the first error, and instead returns every error in every code object
+pub fn validate_module<'a>(region: &'a PyObjectRegion<'a>) -> Vec<ModuleValidationError<'a>> {
+    let mut errors = Vec::new();
+    match region.first() {
+        Some(PyObject::Code(code)) => {
+            validate_module_code(code, region, &mut Vec::new(), &mut Vec::new(), &mut errors)
+        }
+        Some(found) => errors.push(ModuleValidationError {
+            qualname_path: Box::new([]),
+            error: CodeObjectConstructionError::ExpectedModuleCode(found),
+        }),
+        None => errors.push(ModuleValidationError {
+            qualname_path: Box::new([]),
+            error: CodeObjectConstructionError::OutOfBoundsIndex(PyObjectIndex(0)),
+        }),
+    }
+    errors
+}
+
+fn validate_module_code<'a>(
+    code: &'a CodeObjectConstructor,
+    region: &'a PyObjectRegion<'a>,
+    qualname_path: &mut Vec<&'a str>,
+    ancestors: &mut Vec<PyObjectIndex>,
+    errors: &mut Vec<ModuleValidationError<'a>>,
+) {
+    qualname_path.push(code.display_name(region));
+    errors.extend(
+        code.check(region)
+            .into_iter()
+            .map(|error| ModuleValidationError {
+                qualname_path: qualname_path.as_slice().into(),
+                error,
+            }),
+    );
+
+    if let Some(PyObject::Tuple(consts)) = region.get(code.consts) {
+        let mut path = Vec::new();
+        for (i, idx) in consts.iter().enumerate() {
+            path.push(i);
+            let _: ControlFlow<()> = walk_const(
+                *idx,
+                region,
+                &mut path,
+                ancestors,
+                &mut |visit, _, ancestors| {
+                    match visit {
+                        ConstVisit::Code(nested) => {
+                            validate_module_code(nested, region, qualname_path, ancestors, errors)
+                        }
+                        ConstVisit::Invalid(error) => errors.push(ModuleValidationError {
+                            qualname_path: qualname_path.as_slice().into(),
+                            error,
+                        }),
+                    }
+                    Continue(())
+                },
+            );
+            path.pop();
+        }
+    }
+    qualname_path.pop();
+}
+
+/// Returns true if `qualified_name` could be the qualified name of something
+/// called `name`. This is either because they are the same (for things defined
+/// at the top level of a module), or because the qualified name is a dotted
+/// path that ends in the name, like `C.f`, `f.<locals>.g` or
+/// `f.<locals>.<lambda>`
+fn qualified_name_matches(name: &str, qualified_name: &str) -> bool {
+    if name.is_empty() {
+        return false;
+    }
+    match qualified_name.strip_suffix(name) {
+        Some("") => true,
+        Some(parent) => parent.len() > 1 && parent.ends_with('.'),
+        None => false,
+    }
+}
+
+/// Something of interest found while walking a constant
+enum ConstVisit<'a> {
+    /// A nested code object, which is not walked into
+    Code(&'a CodeObjectConstructor),
+    /// Something that can't be a constant
+    Invalid(CodeObjectConstructionError<'a>),
+}
+
+/// Walk the constant at `idx` (reached through `path`), recursing into tuples
+/// and frozensets, and calling `visit` with every code object and every invalid
+/// constant found, along with the path to it. `ancestors` are the constants
+/// currently being walked, which is used to reject constants that contain
+/// themselves. Walking stops early if `visit` breaks
+fn walk_const<'a, B>(
+    idx: PyObjectIndex,
+    region: &'a PyObjectRegion<'a>,
+    path: &mut Vec<usize>,
+    ancestors: &mut Vec<PyObjectIndex>,
+    visit: &mut impl FnMut(ConstVisit<'a>, &[usize], &mut Vec<PyObjectIndex>) -> ControlFlow<B>,
+) -> ControlFlow<B> {
+    use CodeObjectConstructionError as CE;
+    if ancestors.contains(&idx) {
+        return visit(
+            ConstVisit::Invalid(CE::CyclicConst {
+                path: path.as_slice().into(),
+            }),
+            path,
+            ancestors,
+        );
+    }
+    ancestors.push(idx);
+    let flow = match region.get(idx) {
+        None => visit(
+            ConstVisit::Invalid(CE::OutOfBoundsIndex(idx)),
+            path,
+            ancestors,
+        ),
+        Some(PyObject::Code(code)) => visit(ConstVisit::Code(code), path, ancestors),
+        Some(PyObject::Tuple(items) | PyObject::FrozenSet(items)) => {
+            items.iter().enumerate().try_for_each(|(i, item)| {
+                path.push(i);
+                walk_const(*item, region, path, ancestors, visit)?;
+                path.pop();
+                Continue(())
+            })
+        }
+        Some(
+            found @ (PyObject::Null | PyObject::List(_) | PyObject::Dict(_) | PyObject::Set(_)),
+        ) => visit(
+            ConstVisit::Invalid(CE::InvalidConst {
+                path: path.as_slice().into(),
+                found,
+            }),
+            path,
+            ancestors,
+        ),
+        Some(_) => Continue(()),
+    };
+    ancestors.pop();
+    flow
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unmarshal::Unmarshaller;
+    use pretty_assertions::assert_eq;
+
+    const INITIAL_PYC: &[u8] = include_bytes!("../examples/initial.pyc");
+
+    /// Indices of the code objects in the region, in the order they were
+    /// unmarshalled (so the module is first)
+    fn code_indices(region: &PyObjectRegion) -> Vec<PyObjectIndex> {
+        (0..region.0.len())
+            .map(PyObjectIndex)
+            .filter(|idx| matches!(region[*idx], PyObject::Code(_)))
+            .collect()
+    }
+
+    fn code_mut<'r, 'a>(
+        region: &'r mut PyObjectRegion<'a>,
+        idx: PyObjectIndex,
+    ) -> &'r mut CodeObjectConstructor {
+        let PyObject::Code(code) = &mut region.0[idx.0] else {
+            panic!("{idx:?} is not a code object")
+        };
+        code
+    }
+
+    fn construct_root<'a>(
+        region: &'a PyObjectRegion<'a>,
+    ) -> Result<CodeObject<'a>, CodeObjectConstructionError<'a>> {
+        let Some(PyObject::Code(root)) = region.first() else {
+            panic!("Expected the root of the region to be a code object")
+        };
+        root.construct(region)
+    }
+
+    /// Where the nested code object at `idx` is in the root's constants
+    fn const_position(region: &PyObjectRegion, idx: PyObjectIndex) -> usize {
+        let Some(PyObject::Code(root)) = region.first() else {
+            unreachable!()
+        };
+        let PyObject::Tuple(consts) = &region[root.consts] else {
+            unreachable!()
+        };
+        consts.iter().position(|c| *c == idx).unwrap()
+    }
+
+    #[test]
+    fn construct_module_with_nested_code() {
+        let region = Unmarshaller::loads(&INITIAL_PYC[16..]).unwrap();
+        assert_eq!(code_indices(&region).len(), 3);
+        assert!(construct_root(&region).is_ok());
+    }
+
+    #[test]
+    /// Test that the name is actually checked against the qualified name, and
+    /// that the error points at the nested code object it came from
+    fn construct_nested_qualified_name_mismatch() {
+        let mut region = Unmarshaller::loads(&INITIAL_PYC[16..]).unwrap();
+        let [root, nested, _] = code_indices(&region)[..] else {
+            unreachable!()
+        };
+        let root_qualified_name = code_mut(&mut region, root).qualified_name;
+        code_mut(&mut region, nested).qualified_name = root_qualified_name;
+        let position = const_position(&region, nested);
+
+        assert_eq!(
+            construct_root(&region).err(),
+            Some(CodeObjectConstructionError::InConst {
+                path: Box::new([position]),
+                error: Box::new(CodeObjectConstructionError::QualifiedNameMismatch {
+                    name: "f",
+                    qualified_name: "<module>",
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn construct_name_not_string() {
+        let mut region = Unmarshaller::loads(&INITIAL_PYC[16..]).unwrap();
+        let root = code_indices(&region)[0];
+        let code = code_mut(&mut region, root).code;
+        code_mut(&mut region, root).name = code;
+
+        assert!(matches!(
+            construct_root(&region),
+            Err(CodeObjectConstructionError::ExpectedNameString(
+                PyObject::Bytes(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn construct_invalid_and_cyclic_consts() {
+        let mut region = Unmarshaller::loads(&INITIAL_PYC[16..]).unwrap();
+        let root = code_indices(&region)[0];
+        let list = PyObjectIndex(region.0.len());
+        region.0.push(PyObject::List(Box::new([])));
+        let tuple = PyObjectIndex(region.0.len());
+        region.0.push(PyObject::Tuple(Box::new([tuple, list])));
+        let consts = PyObjectIndex(region.0.len());
+        region.0.push(PyObject::Tuple(Box::new([list])));
+        code_mut(&mut region, root).consts = consts;
+
+        assert_eq!(
+            construct_root(&region).err(),
+            Some(CodeObjectConstructionError::InvalidConst {
+                path: Box::new([0]),
+                found: &PyObject::List(Box::new([])),
+            })
+        );
+
+        region.0[consts.0] = PyObject::Tuple(Box::new([tuple]));
+        assert_eq!(
+            construct_root(&region).err(),
+            Some(CodeObjectConstructionError::CyclicConst {
+                path: Box::new([0, 0]),
+            })
+        );
+    }
+
+    #[test]
+    fn validate_valid_module() {
+        let region = Unmarshaller::loads(&INITIAL_PYC[16..]).unwrap();
+        assert_eq!(validate_module(&region), vec![]);
+    }
+
+    #[test]
+    /// Test that keyword-only args aren't counted against the positional ones,
+    /// with `def keyed(*, k=1, j=2)` of examples/eval/literals.py
+    fn validate_keyword_only_args() {
+        let region =
+            Unmarshaller::loads(include_bytes!("../examples/eval/literals.313.marshal")).unwrap();
+        assert_eq!(validate_module(&region), vec![]);
+    }
+
+    #[test]
+    /// Test that every error is reported, rather than just the first, and that
+    /// errors in nested code objects are reported against them
+    fn validate_module_aggregates_errors() {
+        let mut region = Unmarshaller::loads(&INITIAL_PYC[16..]).unwrap();
+        let [root, first, second] = code_indices(&region)[..] else {
+            unreachable!()
+        };
+        code_mut(&mut region, root).first_line_no = 0;
+        code_mut(&mut region, root).stack_size = -1;
+        let root_qualified_name = code_mut(&mut region, root).qualified_name;
+        code_mut(&mut region, first).qualified_name = root_qualified_name;
+        let code = code_mut(&mut region, second).code;
+        code_mut(&mut region, second).filename = code;
+
+        assert_eq!(
+            validate_module(&region),
+            vec![
+                ModuleValidationError {
+                    qualname_path: Box::new(["<module>"]),
+                    error: CodeObjectConstructionError::ExpectedLineNoGtZero(0),
+                },
+                ModuleValidationError {
+                    qualname_path: Box::new(["<module>"]),
+                    error: CodeObjectConstructionError::ExpectedStackSize(-1),
+                },
+                ModuleValidationError {
+                    qualname_path: Box::new(["<module>", "<module>"]),
+                    error: CodeObjectConstructionError::QualifiedNameMismatch {
+                        name: "f",
+                        qualified_name: "<module>",
+                    },
+                },
+                ModuleValidationError {
+                    qualname_path: Box::new(["<module>", "f"]),
+                    error: CodeObjectConstructionError::ExpectedFileNameString(&region[code]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_module_root_not_code() {
+        let region = Unmarshaller::loads(b"N").unwrap();
+        assert_eq!(
+            validate_module(&region),
+            vec![ModuleValidationError {
+                qualname_path: Box::new([]),
+                error: CodeObjectConstructionError::ExpectedModuleCode(&PyObject::None),
+            }]
+        );
+    }
+
+    #[test]
+    fn qualified_names() {
+        assert!(qualified_name_matches("<module>", "<module>"));
+        assert!(qualified_name_matches("f", "f"));
+        assert!(qualified_name_matches("f", "C.f"));
+        assert!(qualified_name_matches("g", "f.<locals>.g"));
+        assert!(qualified_name_matches("<lambda>", "f.<locals>.<lambda>"));
+        assert!(qualified_name_matches(
+            "<genexpr>",
+            "C.f.<locals>.<genexpr>"
+        ));
+        assert!(!qualified_name_matches("g", "f.<locals>.xg"));
+        assert!(!qualified_name_matches("g", ".g"));
+        assert!(!qualified_name_matches("f", "f.<locals>.g"));
+        assert!(!qualified_name_matches("", "f."));
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use super::*;
+    use crate::unmarshal::Unmarshaller;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    /// The JSON shape of a region is documented in the README, so check that
+    /// it doesn't change by accident
+    fn region_json_shape() {
+        let region = PyObjectRegion(vec![
+            PyObject::Tuple(Box::new([PyObjectIndex(1), PyObjectIndex(2)])),
+            PyObject::None,
+            PyObject::SmallInt(5),
+            PyObject::Complex(1.0, 2.0),
+            PyObject::Bytes([0x26, 0x80].as_slice().into()),
+            PyObject::String("x".into()),
+        ]);
+        assert_eq!(
+            serde_json::to_value(&region).unwrap(),
+            serde_json::json!([
+                {"Tuple": [1, 2]},
+                "None",
+                {"SmallInt": 5},
+                {"Complex": [1.0, 2.0]},
+                {"Bytes": [0x26, 0x80]},
+                {"String": "x"},
+            ])
+        );
+    }
+
+    #[test]
+    /// Test that a code object survives a round trip through JSON, using the
+    /// identity function from the unmarshal tests
+    fn code_object_json_round_trip() {
+        let region = Unmarshaller::loads(b"\xe3\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x03\x00\x00\x00\xf3\x06\x00\x00\x00\x80\x00V\x00#\x00)\x01N\xa9\x00)\x01\xda\x01xs\x01\x00\x00\x00&\xda\x07example\xda\x01fr\x05\x00\x00\x00\x01\x00\x00\x00s\x07\x00\x00\x00\x80\x00\xd8\x0b\x0c\x80H\xf3\x00\x00\x00\x00").unwrap();
+        let json = serde_json::to_string(&region).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PyObjectRegion>(&json).unwrap(),
+            region
+        );
     }
 }
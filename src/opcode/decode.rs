@@ -0,0 +1,335 @@
+//! Decoding bytecode into raw instructions, without lowering them into the
+//! stack IR, so every opcode of a version can be decoded
+
+use crate::version::PythonVersion;
+
+use super::{JumpDirection, Opcode, OpcodeTable};
+
+/// A single instruction, with any `EXTENDED_ARG`s before it folded into its
+/// argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawInstruction {
+    /// The offset in bytes of the opcode (after any `EXTENDED_ARG`s)
+    pub offset: usize,
+    pub opcode: Opcode,
+    /// The argument, including the bytes from any `EXTENDED_ARG`s. Opcodes
+    /// that don't take an argument ignore this
+    pub arg: u32,
+    /// The number of `CACHE` code units following the opcode, which are
+    /// skipped
+    pub cache_entries: u8,
+    /// The number of `EXTENDED_ARG`s before the opcode
+    pub extended_arg_prefix_len: u8,
+    /// The offset in bytes that this instruction jumps to, if it can jump
+    pub jump_target: Option<usize>,
+}
+
+impl RawInstruction {
+    /// The offset in bytes of the first `EXTENDED_ARG` before this
+    /// instruction, or of the opcode if there aren't any. Jumps to this
+    /// instruction target this offset
+    pub fn start_offset(&self) -> usize {
+        self.offset - 2 * self.extended_arg_prefix_len as usize
+    }
+
+    /// The offset in bytes of the next instruction, after any inline caches.
+    /// Relative jumps are relative to this
+    pub fn end_offset(&self) -> usize {
+        self.offset + 2 * (1 + self.cache_entries as usize)
+    }
+
+    /// The contents of the inline cache entries of this instruction in the
+    /// code it was decoded from. These are native endian in CPython, which is
+    /// assumed to be little endian. They are zeroed in `.pyc` files and only
+    /// filled in at runtime, but tools looking at a snapshot of live bytecode
+    /// may care about them
+    pub fn cache<'c>(&self, code: &'c [u8]) -> impl Iterator<Item = u16> + 'c {
+        code[self.offset + 2..self.end_offset()]
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The byte at this offset isn't an opcode in the version being decoded
+    InvalidOpcode { offset: usize, byte: u8 },
+    /// There are more `EXTENDED_ARG`s in a row than fit in a 32 bit argument,
+    /// the offset of the first one that doesn't fit is noted
+    ArgExtendWouldOverflow { offset: usize },
+    /// The code ends part way through an instruction or its caches, or after
+    /// an `EXTENDED_ARG`. The offset of the incomplete instruction is noted
+    UnexpectedEnd { offset: usize },
+    /// The backwards jump at this offset jumps to before the start of the code
+    JumpBeforeStart { offset: usize },
+}
+
+/// Iterator over the [`RawInstruction`]s of some bytecode, see [`decode`]
+#[derive(Debug, Clone)]
+pub struct RawInstructions<'c> {
+    table: &'static OpcodeTable,
+    code: &'c [u8],
+    offset: usize,
+}
+
+/// Decode bytecode of the given version into [`RawInstruction`]s. The iterator
+/// stops after the first error
+pub fn decode(version: PythonVersion, code: &[u8]) -> RawInstructions<'_> {
+    RawInstructions {
+        table: OpcodeTable::for_version(version),
+        code,
+        offset: 0,
+    }
+}
+
+impl Iterator for RawInstructions<'_> {
+    type Item = Result<RawInstruction, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.code.len() {
+            return None;
+        }
+        let start = self.offset;
+        let mut arg = 0u32;
+        let mut extended_arg_prefix_len = 0u8;
+        let result = loop {
+            let Some(&[byte, raw_arg]) = self.code.get(self.offset..self.offset + 2) else {
+                break Err(DecodeError::UnexpectedEnd { offset: start });
+            };
+            let Some(info) = self.table.get(byte) else {
+                break Err(DecodeError::InvalidOpcode {
+                    offset: self.offset,
+                    byte,
+                });
+            };
+            arg |= raw_arg as u32;
+            if info.opcode != Opcode::ExtendedArg {
+                let instruction = RawInstruction {
+                    offset: self.offset,
+                    opcode: info.opcode,
+                    arg,
+                    cache_entries: info.cache_entries,
+                    extended_arg_prefix_len,
+                    jump_target: None,
+                };
+                if instruction.end_offset() > self.code.len() {
+                    break Err(DecodeError::UnexpectedEnd { offset: start });
+                }
+                let Some(direction) = info.jump else {
+                    break Ok(instruction);
+                };
+                let delta = match self.table.version().jumps_in_code_units() {
+                    true => arg as usize * 2,
+                    false => arg as usize,
+                };
+                let jump_target = match direction {
+                    JumpDirection::Forward => Some(instruction.end_offset() + delta),
+                    JumpDirection::Backward => instruction.end_offset().checked_sub(delta),
+                    JumpDirection::Absolute => Some(delta),
+                };
+                break match jump_target {
+                    Some(_) => Ok(RawInstruction {
+                        jump_target,
+                        ..instruction
+                    }),
+                    None => Err(DecodeError::JumpBeforeStart {
+                        offset: self.offset,
+                    }),
+                };
+            }
+            if extended_arg_prefix_len == 3 {
+                break Err(DecodeError::ArgExtendWouldOverflow {
+                    offset: self.offset,
+                });
+            }
+            arg <<= 8;
+            extended_arg_prefix_len += 1;
+            self.offset += 2;
+        };
+        match result {
+            Ok(instruction) => self.offset = instruction.end_offset(),
+            Err(_) => self.offset = self.code.len(),
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn byte(version: PythonVersion, opcode: Opcode) -> u8 {
+        OpcodeTable::for_version(version).find(opcode).unwrap().byte
+    }
+
+    #[test]
+    /// Test that every opcode of every version decodes, with its caches skipped
+    fn decode_every_opcode() {
+        for version in PythonVersion::ALL {
+            let table = OpcodeTable::for_version(version);
+            for info in table
+                .iter()
+                .filter(|info| info.opcode != Opcode::ExtendedArg)
+            {
+                let mut code = vec![info.byte, 0];
+                code.resize(2 * (1 + info.cache_entries as usize), 0);
+                let nop = byte(version, Opcode::Nop);
+                code.extend([nop, 0]);
+
+                let nop_offset = code.len() - 2;
+                let jump_target = info.jump.map(|direction| match direction {
+                    JumpDirection::Absolute => 0,
+                    _ => nop_offset,
+                });
+                let decoded: Result<Vec<_>, _> = decode(version, &code).collect();
+                assert_eq!(
+                    decoded,
+                    Ok(vec![
+                        RawInstruction {
+                            offset: 0,
+                            opcode: info.opcode,
+                            arg: 0,
+                            cache_entries: info.cache_entries,
+                            extended_arg_prefix_len: 0,
+                            jump_target,
+                        },
+                        RawInstruction {
+                            offset: nop_offset,
+                            opcode: Opcode::Nop,
+                            arg: 0,
+                            cache_entries: 0,
+                            extended_arg_prefix_len: 0,
+                            jump_target: None,
+                        }
+                    ]),
+                    "{version} {}",
+                    info.opcode.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decode_extended_arg() {
+        let version = PythonVersion::V3_14;
+        let extended_arg = byte(version, Opcode::ExtendedArg);
+        let jump_forward = byte(version, Opcode::JumpForward);
+        let code = [extended_arg, 1, extended_arg, 2, jump_forward, 3];
+        let decoded: Vec<_> = decode(version, &code).collect();
+        let instruction = RawInstruction {
+            offset: 4,
+            opcode: Opcode::JumpForward,
+            arg: 0x010203,
+            cache_entries: 0,
+            extended_arg_prefix_len: 2,
+            jump_target: Some(6 + 2 * 0x010203),
+        };
+        assert_eq!(decoded, vec![Ok(instruction)]);
+        assert_eq!(instruction.start_offset(), 0);
+        assert_eq!(instruction.end_offset(), 6);
+
+        let code = [
+            extended_arg,
+            1,
+            extended_arg,
+            2,
+            extended_arg,
+            3,
+            extended_arg,
+            4,
+            jump_forward,
+            5,
+        ];
+        assert_eq!(
+            decode(version, &code).collect::<Vec<_>>(),
+            vec![Err(DecodeError::ArgExtendWouldOverflow { offset: 6 })]
+        );
+    }
+
+    #[test]
+    fn decode_errors() {
+        let version = PythonVersion::V3_14;
+        let nop = byte(version, Opcode::Nop);
+        let extended_arg = byte(version, Opcode::ExtendedArg);
+        assert_eq!(
+            decode(version, &[nop, 0, 121, 0, nop, 0]).collect::<Vec<_>>(),
+            vec![
+                Ok(RawInstruction {
+                    offset: 0,
+                    opcode: Opcode::Nop,
+                    arg: 0,
+                    cache_entries: 0,
+                    extended_arg_prefix_len: 0,
+                    jump_target: None,
+                }),
+                Err(DecodeError::InvalidOpcode {
+                    offset: 2,
+                    byte: 121
+                })
+            ]
+        );
+        assert_eq!(
+            decode(version, &[extended_arg, 1]).collect::<Vec<_>>(),
+            vec![Err(DecodeError::UnexpectedEnd { offset: 0 })]
+        );
+        assert_eq!(
+            decode(version, &[nop]).collect::<Vec<_>>(),
+            vec![Err(DecodeError::UnexpectedEnd { offset: 0 })]
+        );
+    }
+
+    #[test]
+    /// Test that jump targets account for inline caches, and for whether
+    /// jumps are in bytes or code units
+    fn decode_jumps_and_caches() {
+        let version = PythonVersion::V3_14;
+        let op = |opcode| byte(version, opcode);
+        #[rustfmt::skip]
+        let code = [
+            op(Opcode::Nop), 0,
+            op(Opcode::BinaryOp), 0, 1, 0, 2, 0, 3, 0, 4, 0, 0, 5,
+            op(Opcode::PopJumpIfFalse), 1, 0, 0,
+            op(Opcode::Nop), 0,
+            op(Opcode::JumpBackward), 12, 0, 0,
+        ];
+        let decoded: Vec<_> = decode(version, &code).map(Result::unwrap).collect();
+        assert_eq!(
+            decoded
+                .iter()
+                .map(|i| (i.offset, i.jump_target))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, None),
+                (2, None),
+                (14, Some(20)),
+                (18, None),
+                (20, Some(0))
+            ]
+        );
+        assert_eq!(
+            decoded[1].cache(&code).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 0x500]
+        );
+
+        let mut too_far_back = code;
+        too_far_back[21] = 13;
+        assert_eq!(
+            decode(version, &too_far_back).last(),
+            Some(Err(DecodeError::JumpBeforeStart { offset: 20 }))
+        );
+        assert_eq!(
+            decode(version, &code[..22]).last(),
+            Some(Err(DecodeError::UnexpectedEnd { offset: 20 }))
+        );
+
+        for (version, target) in [(PythonVersion::V3_9, 4), (PythonVersion::V3_10, 8)] {
+            let code = [byte(version, Opcode::JumpAbsolute), 4];
+            assert_eq!(
+                decode(version, &code).next().unwrap().unwrap().jump_target,
+                Some(target)
+            );
+        }
+    }
+}
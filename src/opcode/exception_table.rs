@@ -0,0 +1,131 @@
+/// An entry in the exception table of a code object, with byte offsets into
+/// its bytecode. Exceptions raised by instructions in `start..end` jump to the
+/// handler at `target`, with the stack popped down to depth items (and the
+/// offset of the instruction that raised pushed if `lasti` is set) and the
+/// exception pushed on top
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExceptionTableEntry {
+    pub start: usize,
+    pub end: usize,
+    pub target: usize,
+    pub depth: u32,
+    pub lasti: bool,
+}
+
+/// Parse the `co_exceptiontable` of a 3.11+ code object. Parsing stops at the
+/// first incomplete entry, or one whose offsets overflow
+pub fn parse_exception_table(exception_table: &[u8]) -> Vec<ExceptionTableEntry> {
+    // Unlike the line table, these varints are big endian
+    fn varint(bytes: &mut impl Iterator<Item = u8>) -> Option<usize> {
+        let mut b = bytes.next()?;
+        let mut val = (b & 63) as usize;
+        while b & 64 != 0 {
+            b = bytes.next()?;
+            val = val.checked_mul(64)? | (b & 63) as usize;
+        }
+        Some(val)
+    }
+    // Offsets are in code units
+    fn offset(bytes: &mut impl Iterator<Item = u8>) -> Option<usize> {
+        varint(bytes)?.checked_mul(2)
+    }
+
+    let mut entries = Vec::new();
+    let mut bytes = exception_table.iter().copied();
+    let mut next_entry = || {
+        let start = offset(&mut bytes)?;
+        let end = start.checked_add(offset(&mut bytes)?)?;
+        let target = offset(&mut bytes)?;
+        let depth_lasti = varint(&mut bytes)?;
+        Some(ExceptionTableEntry {
+            start,
+            end,
+            target,
+            depth: u32::try_from(depth_lasti >> 1).ok()?,
+            lasti: depth_lasti & 1 != 0,
+        })
+    };
+    while let Some(entry) = next_entry() {
+        entries.push(entry);
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{objects::PyObject, unmarshal::Unmarshaller};
+    use pretty_assertions::assert_eq;
+
+    fn entry(start: usize, end: usize, target: usize, depth: u32) -> ExceptionTableEntry {
+        ExceptionTableEntry {
+            start,
+            end,
+            target,
+            depth,
+            lasti: depth != 0,
+        }
+    }
+
+    #[test]
+    /// Test the table of `reraised` in examples/eval/handlers.py from 3.13,
+    /// against what `dis._parse_exception_table` gives for it
+    fn parse_compiled_table() {
+        let region =
+            Unmarshaller::loads(include_bytes!("../../examples/eval/handlers.313.marshal"))
+                .unwrap();
+        let Some(table) = region.0.iter().find_map(|object| match object {
+            PyObject::Code(code)
+                if matches!(region.get(code.name), Some(PyObject::String(s)) if s == "reraised") =>
+            {
+                match region.get(code.exception_table) {
+                    Some(PyObject::Bytes(table)) => Some(table),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }) else {
+            panic!("Expected a function called reraised")
+        };
+        assert_eq!(
+            parse_exception_table(table),
+            [
+                entry(6, 20, 24, 0),
+                entry(24, 44, 56, 1),
+                entry(44, 46, 46, 1),
+                entry(46, 56, 56, 1),
+                entry(56, 62, 62, 0),
+                entry(62, 82, 106, 1),
+                entry(82, 84, 96, 1),
+                entry(84, 86, 106, 1),
+                entry(96, 106, 106, 1),
+            ]
+        );
+    }
+
+    #[test]
+    /// Test that parsing stops at an entry that is cut short, or whose offsets
+    /// don't fit in a usize
+    fn parse_malformed_table() {
+        let first = b"\x83\x07\x0c\x00";
+        // The second entry is missing its depth
+        let table = [&first[..], b"\x8c\n\x1c"].concat();
+        assert_eq!(parse_exception_table(&table), [entry(6, 20, 24, 0)]);
+
+        // A start of 64 set bits, which is too big once it's in bytes, and one
+        // with more bits than that
+        for start in [
+            [&b"\x4f"[..], &[0x7f; 9], b"\x3f"].concat(),
+            [&[0x7f; 16][..], b"\x3f"].concat(),
+        ] {
+            let table = [&first[..], &start, b"\x01\x01\x00"].concat();
+            assert_eq!(parse_exception_table(&table), [entry(6, 20, 24, 0)]);
+        }
+
+        // A start and length of 63 set bits, which fit in bytes but overflow
+        // when added
+        let half = [&b"\x47"[..], &[0x7f; 9], b"\x3f"].concat();
+        let table = [&first[..], &half, &half, b"\x01\x00"].concat();
+        assert_eq!(parse_exception_table(&table), [entry(6, 20, 24, 0)]);
+    }
+}
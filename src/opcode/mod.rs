@@ -0,0 +1,467 @@
+//! Opcode metadata for each supported CPython version, modeled on CPython's
+//! `opcode.py` (and `_opcode_metadata.py` since 3.13)
+//!
+//! The same opcode usually has a different byte value in each version, so
+//! [`Opcode`] is the union of the opcodes of every supported version, and an
+//! [`OpcodeTable`] maps the bytes of one version to [`OpcodeInfo`]s.
+//! Specialised and instrumented opcodes are not included, since they are never
+//! written to `.pyc` files
+
+use crate::version::PythonVersion;
+
+mod decode;
+mod exception_table;
+mod tables;
+
+pub use decode::{DecodeError, RawInstruction, RawInstructions, decode};
+pub use exception_table::{ExceptionTableEntry, parse_exception_table};
+
+macro_rules! opcodes {
+    ($($variant:ident => $name:literal,)*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum Opcode {
+            $($variant,)*
+        }
+
+        impl Opcode {
+            /// The name CPython uses for this opcode, e.g. `LOAD_CONST`
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Opcode::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+opcodes! {
+    AsyncGenWrap => "ASYNC_GEN_WRAP",
+    BeforeAsyncWith => "BEFORE_ASYNC_WITH",
+    BeforeWith => "BEFORE_WITH",
+    BeginFinally => "BEGIN_FINALLY",
+    BinaryAdd => "BINARY_ADD",
+    BinaryAnd => "BINARY_AND",
+    BinaryFloorDivide => "BINARY_FLOOR_DIVIDE",
+    BinaryLshift => "BINARY_LSHIFT",
+    BinaryMatrixMultiply => "BINARY_MATRIX_MULTIPLY",
+    BinaryModulo => "BINARY_MODULO",
+    BinaryMultiply => "BINARY_MULTIPLY",
+    BinaryOp => "BINARY_OP",
+    BinaryOr => "BINARY_OR",
+    BinaryPower => "BINARY_POWER",
+    BinaryRshift => "BINARY_RSHIFT",
+    BinarySlice => "BINARY_SLICE",
+    BinarySubscr => "BINARY_SUBSCR",
+    BinarySubtract => "BINARY_SUBTRACT",
+    BinaryTrueDivide => "BINARY_TRUE_DIVIDE",
+    BinaryXor => "BINARY_XOR",
+    BuildConstKeyMap => "BUILD_CONST_KEY_MAP",
+    BuildInterpolation => "BUILD_INTERPOLATION",
+    BuildList => "BUILD_LIST",
+    BuildListUnpack => "BUILD_LIST_UNPACK",
+    BuildMap => "BUILD_MAP",
+    BuildMapUnpack => "BUILD_MAP_UNPACK",
+    BuildMapUnpackWithCall => "BUILD_MAP_UNPACK_WITH_CALL",
+    BuildSet => "BUILD_SET",
+    BuildSetUnpack => "BUILD_SET_UNPACK",
+    BuildSlice => "BUILD_SLICE",
+    BuildString => "BUILD_STRING",
+    BuildTemplate => "BUILD_TEMPLATE",
+    BuildTuple => "BUILD_TUPLE",
+    BuildTupleUnpack => "BUILD_TUPLE_UNPACK",
+    BuildTupleUnpackWithCall => "BUILD_TUPLE_UNPACK_WITH_CALL",
+    Cache => "CACHE",
+    Call => "CALL",
+    CallFinally => "CALL_FINALLY",
+    CallFunction => "CALL_FUNCTION",
+    CallFunctionEx => "CALL_FUNCTION_EX",
+    CallFunctionKw => "CALL_FUNCTION_KW",
+    CallIntrinsic1 => "CALL_INTRINSIC_1",
+    CallIntrinsic2 => "CALL_INTRINSIC_2",
+    CallKw => "CALL_KW",
+    CallMethod => "CALL_METHOD",
+    CheckEgMatch => "CHECK_EG_MATCH",
+    CheckExcMatch => "CHECK_EXC_MATCH",
+    CleanupThrow => "CLEANUP_THROW",
+    CompareOp => "COMPARE_OP",
+    ContainsOp => "CONTAINS_OP",
+    ConvertValue => "CONVERT_VALUE",
+    Copy => "COPY",
+    CopyDictWithoutKeys => "COPY_DICT_WITHOUT_KEYS",
+    CopyFreeVars => "COPY_FREE_VARS",
+    DeleteAttr => "DELETE_ATTR",
+    DeleteDeref => "DELETE_DEREF",
+    DeleteFast => "DELETE_FAST",
+    DeleteGlobal => "DELETE_GLOBAL",
+    DeleteName => "DELETE_NAME",
+    DeleteSubscr => "DELETE_SUBSCR",
+    DictMerge => "DICT_MERGE",
+    DictUpdate => "DICT_UPDATE",
+    DupTop => "DUP_TOP",
+    DupTopTwo => "DUP_TOP_TWO",
+    EndAsyncFor => "END_ASYNC_FOR",
+    EndFinally => "END_FINALLY",
+    EndFor => "END_FOR",
+    EndSend => "END_SEND",
+    ExitInitCheck => "EXIT_INIT_CHECK",
+    ExtendedArg => "EXTENDED_ARG",
+    FormatSimple => "FORMAT_SIMPLE",
+    FormatValue => "FORMAT_VALUE",
+    FormatWithSpec => "FORMAT_WITH_SPEC",
+    ForIter => "FOR_ITER",
+    GenStart => "GEN_START",
+    GetAiter => "GET_AITER",
+    GetAnext => "GET_ANEXT",
+    GetAwaitable => "GET_AWAITABLE",
+    GetIter => "GET_ITER",
+    GetLen => "GET_LEN",
+    GetYieldFromIter => "GET_YIELD_FROM_ITER",
+    ImportFrom => "IMPORT_FROM",
+    ImportName => "IMPORT_NAME",
+    ImportStar => "IMPORT_STAR",
+    InplaceAdd => "INPLACE_ADD",
+    InplaceAnd => "INPLACE_AND",
+    InplaceFloorDivide => "INPLACE_FLOOR_DIVIDE",
+    InplaceLshift => "INPLACE_LSHIFT",
+    InplaceMatrixMultiply => "INPLACE_MATRIX_MULTIPLY",
+    InplaceModulo => "INPLACE_MODULO",
+    InplaceMultiply => "INPLACE_MULTIPLY",
+    InplaceOr => "INPLACE_OR",
+    InplacePower => "INPLACE_POWER",
+    InplaceRshift => "INPLACE_RSHIFT",
+    InplaceSubtract => "INPLACE_SUBTRACT",
+    InplaceTrueDivide => "INPLACE_TRUE_DIVIDE",
+    InplaceXor => "INPLACE_XOR",
+    InterpreterExit => "INTERPRETER_EXIT",
+    IsOp => "IS_OP",
+    JumpAbsolute => "JUMP_ABSOLUTE",
+    JumpBackward => "JUMP_BACKWARD",
+    JumpBackwardNoInterrupt => "JUMP_BACKWARD_NO_INTERRUPT",
+    JumpForward => "JUMP_FORWARD",
+    JumpIfFalseOrPop => "JUMP_IF_FALSE_OR_POP",
+    JumpIfNotExcMatch => "JUMP_IF_NOT_EXC_MATCH",
+    JumpIfTrueOrPop => "JUMP_IF_TRUE_OR_POP",
+    KwNames => "KW_NAMES",
+    ListAppend => "LIST_APPEND",
+    ListExtend => "LIST_EXTEND",
+    ListToTuple => "LIST_TO_TUPLE",
+    LoadAssertionError => "LOAD_ASSERTION_ERROR",
+    LoadAttr => "LOAD_ATTR",
+    LoadBuildClass => "LOAD_BUILD_CLASS",
+    LoadClassderef => "LOAD_CLASSDEREF",
+    LoadClosure => "LOAD_CLOSURE",
+    LoadCommonConstant => "LOAD_COMMON_CONSTANT",
+    LoadConst => "LOAD_CONST",
+    LoadDeref => "LOAD_DEREF",
+    LoadFast => "LOAD_FAST",
+    LoadFastAndClear => "LOAD_FAST_AND_CLEAR",
+    LoadFastBorrow => "LOAD_FAST_BORROW",
+    LoadFastBorrowLoadFastBorrow => "LOAD_FAST_BORROW_LOAD_FAST_BORROW",
+    LoadFastCheck => "LOAD_FAST_CHECK",
+    LoadFastLoadFast => "LOAD_FAST_LOAD_FAST",
+    LoadFromDictOrDeref => "LOAD_FROM_DICT_OR_DEREF",
+    LoadFromDictOrGlobals => "LOAD_FROM_DICT_OR_GLOBALS",
+    LoadGlobal => "LOAD_GLOBAL",
+    LoadLocals => "LOAD_LOCALS",
+    LoadMethod => "LOAD_METHOD",
+    LoadName => "LOAD_NAME",
+    LoadSmallInt => "LOAD_SMALL_INT",
+    LoadSpecial => "LOAD_SPECIAL",
+    LoadSuperAttr => "LOAD_SUPER_ATTR",
+    MakeCell => "MAKE_CELL",
+    MakeFunction => "MAKE_FUNCTION",
+    MapAdd => "MAP_ADD",
+    MatchClass => "MATCH_CLASS",
+    MatchKeys => "MATCH_KEYS",
+    MatchMapping => "MATCH_MAPPING",
+    MatchSequence => "MATCH_SEQUENCE",
+    Nop => "NOP",
+    NotTaken => "NOT_TAKEN",
+    PopBlock => "POP_BLOCK",
+    PopExcept => "POP_EXCEPT",
+    PopFinally => "POP_FINALLY",
+    PopIter => "POP_ITER",
+    PopJumpBackwardIfFalse => "POP_JUMP_BACKWARD_IF_FALSE",
+    PopJumpBackwardIfNone => "POP_JUMP_BACKWARD_IF_NONE",
+    PopJumpBackwardIfNotNone => "POP_JUMP_BACKWARD_IF_NOT_NONE",
+    PopJumpBackwardIfTrue => "POP_JUMP_BACKWARD_IF_TRUE",
+    PopJumpForwardIfFalse => "POP_JUMP_FORWARD_IF_FALSE",
+    PopJumpForwardIfNone => "POP_JUMP_FORWARD_IF_NONE",
+    PopJumpForwardIfNotNone => "POP_JUMP_FORWARD_IF_NOT_NONE",
+    PopJumpForwardIfTrue => "POP_JUMP_FORWARD_IF_TRUE",
+    PopJumpIfFalse => "POP_JUMP_IF_FALSE",
+    PopJumpIfNone => "POP_JUMP_IF_NONE",
+    PopJumpIfNotNone => "POP_JUMP_IF_NOT_NONE",
+    PopJumpIfTrue => "POP_JUMP_IF_TRUE",
+    PopTop => "POP_TOP",
+    Precall => "PRECALL",
+    PrepReraiseStar => "PREP_RERAISE_STAR",
+    PrintExpr => "PRINT_EXPR",
+    PushExcInfo => "PUSH_EXC_INFO",
+    PushNull => "PUSH_NULL",
+    RaiseVarargs => "RAISE_VARARGS",
+    Reraise => "RERAISE",
+    Reserved => "RESERVED",
+    Resume => "RESUME",
+    ReturnConst => "RETURN_CONST",
+    ReturnGenerator => "RETURN_GENERATOR",
+    ReturnValue => "RETURN_VALUE",
+    RotFour => "ROT_FOUR",
+    RotN => "ROT_N",
+    RotThree => "ROT_THREE",
+    RotTwo => "ROT_TWO",
+    Send => "SEND",
+    SetupAnnotations => "SETUP_ANNOTATIONS",
+    SetupAsyncWith => "SETUP_ASYNC_WITH",
+    SetupFinally => "SETUP_FINALLY",
+    SetupWith => "SETUP_WITH",
+    SetAdd => "SET_ADD",
+    SetFunctionAttribute => "SET_FUNCTION_ATTRIBUTE",
+    SetUpdate => "SET_UPDATE",
+    StoreAttr => "STORE_ATTR",
+    StoreDeref => "STORE_DEREF",
+    StoreFast => "STORE_FAST",
+    StoreFastLoadFast => "STORE_FAST_LOAD_FAST",
+    StoreFastStoreFast => "STORE_FAST_STORE_FAST",
+    StoreGlobal => "STORE_GLOBAL",
+    StoreName => "STORE_NAME",
+    StoreSlice => "STORE_SLICE",
+    StoreSubscr => "STORE_SUBSCR",
+    Swap => "SWAP",
+    ToBool => "TO_BOOL",
+    UnaryInvert => "UNARY_INVERT",
+    UnaryNegative => "UNARY_NEGATIVE",
+    UnaryNot => "UNARY_NOT",
+    UnaryPositive => "UNARY_POSITIVE",
+    UnpackEx => "UNPACK_EX",
+    UnpackSequence => "UNPACK_SEQUENCE",
+    WithCleanupFinish => "WITH_CLEANUP_FINISH",
+    WithCleanupStart => "WITH_CLEANUP_START",
+    WithExceptStart => "WITH_EXCEPT_START",
+    YieldFrom => "YIELD_FROM",
+    YieldValue => "YIELD_VALUE",
+}
+
+/// What the argument of an opcode refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    /// The opcode doesn't take an argument (and any argument is ignored)
+    Unused,
+    /// An index into `co_consts`
+    Const,
+    /// An index into `co_names`
+    Name,
+    /// An index into the local variables (`co_varnames` before 3.11 and the
+    /// locals plus names since)
+    Local,
+    /// An index into the cell and free variables (since 3.11, this is an index
+    /// into the locals plus names)
+    Free,
+    /// A jump delta (or absolute target), see [`OpcodeInfo::jump`]
+    Jump,
+    /// A comparison operator
+    Compare,
+    /// Anything else, e.g. a count or a flag set
+    Other,
+}
+
+/// Which way a jump opcode's argument points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JumpDirection {
+    /// The argument is added to the offset of the next instruction (after any
+    /// inline cache entries)
+    Forward,
+    /// The argument is subtracted from the offset of the next instruction
+    /// (after any inline cache entries)
+    Backward,
+    /// The argument is the target offset
+    Absolute,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeInfo {
+    pub opcode: Opcode,
+    pub byte: u8,
+    /// Whether the opcode uses its argument (`opcode >= HAVE_ARGUMENT` before
+    /// 3.13)
+    pub has_arg: bool,
+    pub arg_kind: ArgKind,
+    /// The number of `CACHE` code units following the opcode (always 0 before
+    /// 3.11)
+    pub cache_entries: u8,
+    /// The direction of the jump if this opcode can jump
+    pub jump: Option<JumpDirection>,
+}
+
+/// Used by the tables to describe an opcode that doesn't take an argument
+const fn op(byte: u8, opcode: Opcode) -> OpcodeInfo {
+    OpcodeInfo {
+        opcode,
+        byte,
+        has_arg: false,
+        arg_kind: ArgKind::Unused,
+        cache_entries: 0,
+        jump: None,
+    }
+}
+
+impl OpcodeInfo {
+    const fn arg(self, arg_kind: ArgKind) -> OpcodeInfo {
+        OpcodeInfo {
+            has_arg: true,
+            arg_kind,
+            ..self
+        }
+    }
+
+    const fn jump(self, direction: JumpDirection) -> OpcodeInfo {
+        OpcodeInfo {
+            has_arg: true,
+            arg_kind: ArgKind::Jump,
+            jump: Some(direction),
+            ..self
+        }
+    }
+
+    const fn cache(self, cache_entries: u8) -> OpcodeInfo {
+        OpcodeInfo {
+            cache_entries,
+            ..self
+        }
+    }
+}
+
+/// Maps the byte values of one version's opcodes to their metadata
+#[derive(Debug)]
+pub struct OpcodeTable {
+    version: PythonVersion,
+    ops: [Option<OpcodeInfo>; 256],
+}
+
+impl OpcodeTable {
+    const fn new(version: PythonVersion, entries: &[OpcodeInfo]) -> OpcodeTable {
+        let mut ops = [None; 256];
+        let mut i = 0;
+        while i < entries.len() {
+            ops[entries[i].byte as usize] = Some(entries[i]);
+            i += 1;
+        }
+        OpcodeTable { version, ops }
+    }
+
+    pub fn for_version(version: PythonVersion) -> &'static OpcodeTable {
+        match version {
+            PythonVersion::V3_8 => &tables::PY38,
+            PythonVersion::V3_9 => &tables::PY39,
+            PythonVersion::V3_10 => &tables::PY310,
+            PythonVersion::V3_11 => &tables::PY311,
+            PythonVersion::V3_12 => &tables::PY312,
+            PythonVersion::V3_13 => &tables::PY313,
+            PythonVersion::V3_14 => &tables::PY314,
+        }
+    }
+
+    pub fn version(&self) -> PythonVersion {
+        self.version
+    }
+
+    /// Get the metadata of the opcode with this byte value, or None if it isn't
+    /// an opcode in this version
+    pub fn get(&self, byte: u8) -> Option<&OpcodeInfo> {
+        self.ops[byte as usize].as_ref()
+    }
+
+    /// Get the metadata of an opcode by name, or None if it doesn't exist in
+    /// this version
+    pub fn find(&self, opcode: Opcode) -> Option<&OpcodeInfo> {
+        self.iter().find(|info| info.opcode == opcode)
+    }
+
+    /// Iterate over every opcode in this version in byte order
+    pub fn iter(&self) -> impl Iterator<Item = &OpcodeInfo> {
+        self.ops.iter().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    /// Test that the byte stored in each entry is the one it's looked up by
+    fn table_bytes_consistent() {
+        for version in PythonVersion::ALL {
+            let table = OpcodeTable::for_version(version);
+            assert_eq!(table.version(), version);
+            for byte in 0..=255 {
+                if let Some(info) = table.get(byte) {
+                    assert_eq!(info.byte, byte, "{version} {}", info.opcode.name());
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Test that no version has the same opcode at two different bytes
+    fn table_opcodes_unique() {
+        for version in PythonVersion::ALL {
+            let table = OpcodeTable::for_version(version);
+            for info in table.iter() {
+                assert_eq!(
+                    table.find(info.opcode).map(|i| i.byte),
+                    Some(info.byte),
+                    "{version} {}",
+                    info.opcode.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    /// Spot check some values that have changed between versions against
+    /// `python -m dis` / `opcode.py`
+    fn table_spot_checks() {
+        let load_const = |version| {
+            OpcodeTable::for_version(version)
+                .find(Opcode::LoadConst)
+                .unwrap()
+                .byte
+        };
+        assert_eq!(load_const(PythonVersion::V3_8), 100);
+        assert_eq!(load_const(PythonVersion::V3_12), 100);
+        assert_eq!(load_const(PythonVersion::V3_13), 83);
+        assert_eq!(load_const(PythonVersion::V3_14), 82);
+
+        let py314 = OpcodeTable::for_version(PythonVersion::V3_14);
+        assert_eq!(
+            py314.get(44),
+            Some(&OpcodeInfo {
+                opcode: Opcode::BinaryOp,
+                byte: 44,
+                has_arg: true,
+                arg_kind: ArgKind::Other,
+                cache_entries: 5,
+                jump: None,
+            })
+        );
+        assert_eq!(py314.get(75).unwrap().jump, Some(JumpDirection::Backward));
+        assert_eq!(py314.get(0).unwrap().opcode, Opcode::Cache);
+        assert_eq!(py314.get(121), None);
+
+        let py39 = OpcodeTable::for_version(PythonVersion::V3_9);
+        assert_eq!(
+            py39.find(Opcode::PopJumpIfFalse).unwrap().jump,
+            Some(JumpDirection::Absolute)
+        );
+        assert_eq!(py39.find(Opcode::Cache), None);
+
+        let py311 = OpcodeTable::for_version(PythonVersion::V3_11);
+        assert_eq!(py311.find(Opcode::LoadGlobal).unwrap().cache_entries, 5);
+        assert_eq!(
+            py311.find(Opcode::LoadGlobal).unwrap().arg_kind,
+            ArgKind::Name
+        );
+    }
+}
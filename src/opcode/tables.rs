@@ -0,0 +1,874 @@
+//! The opcode tables for each version. These are transcribed from the
+//! `opcode` module of each CPython version, with the inline cache sizes taken
+//! from `_inline_cache_entries`
+
+use super::{ArgKind as A, JumpDirection as J, Opcode::*, OpcodeTable, op};
+use crate::version::PythonVersion;
+
+pub(super) static PY38: OpcodeTable = OpcodeTable::new(
+    PythonVersion::V3_8,
+    &[
+        op(1, PopTop),
+        op(2, RotTwo),
+        op(3, RotThree),
+        op(4, DupTop),
+        op(5, DupTopTwo),
+        op(6, RotFour),
+        op(9, Nop),
+        op(10, UnaryPositive),
+        op(11, UnaryNegative),
+        op(12, UnaryNot),
+        op(15, UnaryInvert),
+        op(16, BinaryMatrixMultiply),
+        op(17, InplaceMatrixMultiply),
+        op(19, BinaryPower),
+        op(20, BinaryMultiply),
+        op(22, BinaryModulo),
+        op(23, BinaryAdd),
+        op(24, BinarySubtract),
+        op(25, BinarySubscr),
+        op(26, BinaryFloorDivide),
+        op(27, BinaryTrueDivide),
+        op(28, InplaceFloorDivide),
+        op(29, InplaceTrueDivide),
+        op(50, GetAiter),
+        op(51, GetAnext),
+        op(52, BeforeAsyncWith),
+        op(53, BeginFinally),
+        op(54, EndAsyncFor),
+        op(55, InplaceAdd),
+        op(56, InplaceSubtract),
+        op(57, InplaceMultiply),
+        op(59, InplaceModulo),
+        op(60, StoreSubscr),
+        op(61, DeleteSubscr),
+        op(62, BinaryLshift),
+        op(63, BinaryRshift),
+        op(64, BinaryAnd),
+        op(65, BinaryXor),
+        op(66, BinaryOr),
+        op(67, InplacePower),
+        op(68, GetIter),
+        op(69, GetYieldFromIter),
+        op(70, PrintExpr),
+        op(71, LoadBuildClass),
+        op(72, YieldFrom),
+        op(73, GetAwaitable),
+        op(75, InplaceLshift),
+        op(76, InplaceRshift),
+        op(77, InplaceAnd),
+        op(78, InplaceXor),
+        op(79, InplaceOr),
+        op(81, WithCleanupStart),
+        op(82, WithCleanupFinish),
+        op(83, ReturnValue),
+        op(84, ImportStar),
+        op(85, SetupAnnotations),
+        op(86, YieldValue),
+        op(87, PopBlock),
+        op(88, EndFinally),
+        op(89, PopExcept),
+        op(90, StoreName).arg(A::Name),
+        op(91, DeleteName).arg(A::Name),
+        op(92, UnpackSequence).arg(A::Other),
+        op(93, ForIter).jump(J::Forward),
+        op(94, UnpackEx).arg(A::Other),
+        op(95, StoreAttr).arg(A::Name),
+        op(96, DeleteAttr).arg(A::Name),
+        op(97, StoreGlobal).arg(A::Name),
+        op(98, DeleteGlobal).arg(A::Name),
+        op(100, LoadConst).arg(A::Const),
+        op(101, LoadName).arg(A::Name),
+        op(102, BuildTuple).arg(A::Other),
+        op(103, BuildList).arg(A::Other),
+        op(104, BuildSet).arg(A::Other),
+        op(105, BuildMap).arg(A::Other),
+        op(106, LoadAttr).arg(A::Name),
+        op(107, CompareOp).arg(A::Compare),
+        op(108, ImportName).arg(A::Name),
+        op(109, ImportFrom).arg(A::Name),
+        op(110, JumpForward).jump(J::Forward),
+        op(111, JumpIfFalseOrPop).jump(J::Absolute),
+        op(112, JumpIfTrueOrPop).jump(J::Absolute),
+        op(113, JumpAbsolute).jump(J::Absolute),
+        op(114, PopJumpIfFalse).jump(J::Absolute),
+        op(115, PopJumpIfTrue).jump(J::Absolute),
+        op(116, LoadGlobal).arg(A::Name),
+        op(122, SetupFinally).jump(J::Forward),
+        op(124, LoadFast).arg(A::Local),
+        op(125, StoreFast).arg(A::Local),
+        op(126, DeleteFast).arg(A::Local),
+        op(130, RaiseVarargs).arg(A::Other),
+        op(131, CallFunction).arg(A::Other),
+        op(132, MakeFunction).arg(A::Other),
+        op(133, BuildSlice).arg(A::Other),
+        op(135, LoadClosure).arg(A::Free),
+        op(136, LoadDeref).arg(A::Free),
+        op(137, StoreDeref).arg(A::Free),
+        op(138, DeleteDeref).arg(A::Free),
+        op(141, CallFunctionKw).arg(A::Other),
+        op(142, CallFunctionEx).arg(A::Other),
+        op(143, SetupWith).jump(J::Forward),
+        op(144, ExtendedArg).arg(A::Other),
+        op(145, ListAppend).arg(A::Other),
+        op(146, SetAdd).arg(A::Other),
+        op(147, MapAdd).arg(A::Other),
+        op(148, LoadClassderef).arg(A::Free),
+        op(149, BuildListUnpack).arg(A::Other),
+        op(150, BuildMapUnpack).arg(A::Other),
+        op(151, BuildMapUnpackWithCall).arg(A::Other),
+        op(152, BuildTupleUnpack).arg(A::Other),
+        op(153, BuildSetUnpack).arg(A::Other),
+        op(154, SetupAsyncWith).jump(J::Forward),
+        op(155, FormatValue).arg(A::Other),
+        op(156, BuildConstKeyMap).arg(A::Other),
+        op(157, BuildString).arg(A::Other),
+        op(158, BuildTupleUnpackWithCall).arg(A::Other),
+        op(160, LoadMethod).arg(A::Name),
+        op(161, CallMethod).arg(A::Other),
+        op(162, CallFinally).jump(J::Forward),
+        op(163, PopFinally).arg(A::Other),
+    ],
+);
+
+pub(super) static PY39: OpcodeTable = OpcodeTable::new(
+    PythonVersion::V3_9,
+    &[
+        op(1, PopTop),
+        op(2, RotTwo),
+        op(3, RotThree),
+        op(4, DupTop),
+        op(5, DupTopTwo),
+        op(6, RotFour),
+        op(9, Nop),
+        op(10, UnaryPositive),
+        op(11, UnaryNegative),
+        op(12, UnaryNot),
+        op(15, UnaryInvert),
+        op(16, BinaryMatrixMultiply),
+        op(17, InplaceMatrixMultiply),
+        op(19, BinaryPower),
+        op(20, BinaryMultiply),
+        op(22, BinaryModulo),
+        op(23, BinaryAdd),
+        op(24, BinarySubtract),
+        op(25, BinarySubscr),
+        op(26, BinaryFloorDivide),
+        op(27, BinaryTrueDivide),
+        op(28, InplaceFloorDivide),
+        op(29, InplaceTrueDivide),
+        op(48, Reraise),
+        op(49, WithExceptStart),
+        op(50, GetAiter),
+        op(51, GetAnext),
+        op(52, BeforeAsyncWith),
+        op(54, EndAsyncFor),
+        op(55, InplaceAdd),
+        op(56, InplaceSubtract),
+        op(57, InplaceMultiply),
+        op(59, InplaceModulo),
+        op(60, StoreSubscr),
+        op(61, DeleteSubscr),
+        op(62, BinaryLshift),
+        op(63, BinaryRshift),
+        op(64, BinaryAnd),
+        op(65, BinaryXor),
+        op(66, BinaryOr),
+        op(67, InplacePower),
+        op(68, GetIter),
+        op(69, GetYieldFromIter),
+        op(70, PrintExpr),
+        op(71, LoadBuildClass),
+        op(72, YieldFrom),
+        op(73, GetAwaitable),
+        op(74, LoadAssertionError),
+        op(75, InplaceLshift),
+        op(76, InplaceRshift),
+        op(77, InplaceAnd),
+        op(78, InplaceXor),
+        op(79, InplaceOr),
+        op(82, ListToTuple),
+        op(83, ReturnValue),
+        op(84, ImportStar),
+        op(85, SetupAnnotations),
+        op(86, YieldValue),
+        op(87, PopBlock),
+        op(89, PopExcept),
+        op(90, StoreName).arg(A::Name),
+        op(91, DeleteName).arg(A::Name),
+        op(92, UnpackSequence).arg(A::Other),
+        op(93, ForIter).jump(J::Forward),
+        op(94, UnpackEx).arg(A::Other),
+        op(95, StoreAttr).arg(A::Name),
+        op(96, DeleteAttr).arg(A::Name),
+        op(97, StoreGlobal).arg(A::Name),
+        op(98, DeleteGlobal).arg(A::Name),
+        op(100, LoadConst).arg(A::Const),
+        op(101, LoadName).arg(A::Name),
+        op(102, BuildTuple).arg(A::Other),
+        op(103, BuildList).arg(A::Other),
+        op(104, BuildSet).arg(A::Other),
+        op(105, BuildMap).arg(A::Other),
+        op(106, LoadAttr).arg(A::Name),
+        op(107, CompareOp).arg(A::Compare),
+        op(108, ImportName).arg(A::Name),
+        op(109, ImportFrom).arg(A::Name),
+        op(110, JumpForward).jump(J::Forward),
+        op(111, JumpIfFalseOrPop).jump(J::Absolute),
+        op(112, JumpIfTrueOrPop).jump(J::Absolute),
+        op(113, JumpAbsolute).jump(J::Absolute),
+        op(114, PopJumpIfFalse).jump(J::Absolute),
+        op(115, PopJumpIfTrue).jump(J::Absolute),
+        op(116, LoadGlobal).arg(A::Name),
+        op(117, IsOp).arg(A::Other),
+        op(118, ContainsOp).arg(A::Other),
+        op(121, JumpIfNotExcMatch).jump(J::Absolute),
+        op(122, SetupFinally).jump(J::Forward),
+        op(124, LoadFast).arg(A::Local),
+        op(125, StoreFast).arg(A::Local),
+        op(126, DeleteFast).arg(A::Local),
+        op(130, RaiseVarargs).arg(A::Other),
+        op(131, CallFunction).arg(A::Other),
+        op(132, MakeFunction).arg(A::Other),
+        op(133, BuildSlice).arg(A::Other),
+        op(135, LoadClosure).arg(A::Free),
+        op(136, LoadDeref).arg(A::Free),
+        op(137, StoreDeref).arg(A::Free),
+        op(138, DeleteDeref).arg(A::Free),
+        op(141, CallFunctionKw).arg(A::Other),
+        op(142, CallFunctionEx).arg(A::Other),
+        op(143, SetupWith).jump(J::Forward),
+        op(144, ExtendedArg).arg(A::Other),
+        op(145, ListAppend).arg(A::Other),
+        op(146, SetAdd).arg(A::Other),
+        op(147, MapAdd).arg(A::Other),
+        op(148, LoadClassderef).arg(A::Free),
+        op(154, SetupAsyncWith).jump(J::Forward),
+        op(155, FormatValue).arg(A::Other),
+        op(156, BuildConstKeyMap).arg(A::Other),
+        op(157, BuildString).arg(A::Other),
+        op(160, LoadMethod).arg(A::Name),
+        op(161, CallMethod).arg(A::Other),
+        op(162, ListExtend).arg(A::Other),
+        op(163, SetUpdate).arg(A::Other),
+        op(164, DictMerge).arg(A::Other),
+        op(165, DictUpdate).arg(A::Other),
+    ],
+);
+
+pub(super) static PY310: OpcodeTable = OpcodeTable::new(
+    PythonVersion::V3_10,
+    &[
+        op(1, PopTop),
+        op(2, RotTwo),
+        op(3, RotThree),
+        op(4, DupTop),
+        op(5, DupTopTwo),
+        op(6, RotFour),
+        op(9, Nop),
+        op(10, UnaryPositive),
+        op(11, UnaryNegative),
+        op(12, UnaryNot),
+        op(15, UnaryInvert),
+        op(16, BinaryMatrixMultiply),
+        op(17, InplaceMatrixMultiply),
+        op(19, BinaryPower),
+        op(20, BinaryMultiply),
+        op(22, BinaryModulo),
+        op(23, BinaryAdd),
+        op(24, BinarySubtract),
+        op(25, BinarySubscr),
+        op(26, BinaryFloorDivide),
+        op(27, BinaryTrueDivide),
+        op(28, InplaceFloorDivide),
+        op(29, InplaceTrueDivide),
+        op(30, GetLen),
+        op(31, MatchMapping),
+        op(32, MatchSequence),
+        op(33, MatchKeys),
+        op(34, CopyDictWithoutKeys),
+        op(49, WithExceptStart),
+        op(50, GetAiter),
+        op(51, GetAnext),
+        op(52, BeforeAsyncWith),
+        op(54, EndAsyncFor),
+        op(55, InplaceAdd),
+        op(56, InplaceSubtract),
+        op(57, InplaceMultiply),
+        op(59, InplaceModulo),
+        op(60, StoreSubscr),
+        op(61, DeleteSubscr),
+        op(62, BinaryLshift),
+        op(63, BinaryRshift),
+        op(64, BinaryAnd),
+        op(65, BinaryXor),
+        op(66, BinaryOr),
+        op(67, InplacePower),
+        op(68, GetIter),
+        op(69, GetYieldFromIter),
+        op(70, PrintExpr),
+        op(71, LoadBuildClass),
+        op(72, YieldFrom),
+        op(73, GetAwaitable),
+        op(74, LoadAssertionError),
+        op(75, InplaceLshift),
+        op(76, InplaceRshift),
+        op(77, InplaceAnd),
+        op(78, InplaceXor),
+        op(79, InplaceOr),
+        op(82, ListToTuple),
+        op(83, ReturnValue),
+        op(84, ImportStar),
+        op(85, SetupAnnotations),
+        op(86, YieldValue),
+        op(87, PopBlock),
+        op(89, PopExcept),
+        op(90, StoreName).arg(A::Name),
+        op(91, DeleteName).arg(A::Name),
+        op(92, UnpackSequence).arg(A::Other),
+        op(93, ForIter).jump(J::Forward),
+        op(94, UnpackEx).arg(A::Other),
+        op(95, StoreAttr).arg(A::Name),
+        op(96, DeleteAttr).arg(A::Name),
+        op(97, StoreGlobal).arg(A::Name),
+        op(98, DeleteGlobal).arg(A::Name),
+        op(99, RotN).arg(A::Other),
+        op(100, LoadConst).arg(A::Const),
+        op(101, LoadName).arg(A::Name),
+        op(102, BuildTuple).arg(A::Other),
+        op(103, BuildList).arg(A::Other),
+        op(104, BuildSet).arg(A::Other),
+        op(105, BuildMap).arg(A::Other),
+        op(106, LoadAttr).arg(A::Name),
+        op(107, CompareOp).arg(A::Compare),
+        op(108, ImportName).arg(A::Name),
+        op(109, ImportFrom).arg(A::Name),
+        op(110, JumpForward).jump(J::Forward),
+        op(111, JumpIfFalseOrPop).jump(J::Absolute),
+        op(112, JumpIfTrueOrPop).jump(J::Absolute),
+        op(113, JumpAbsolute).jump(J::Absolute),
+        op(114, PopJumpIfFalse).jump(J::Absolute),
+        op(115, PopJumpIfTrue).jump(J::Absolute),
+        op(116, LoadGlobal).arg(A::Name),
+        op(117, IsOp).arg(A::Other),
+        op(118, ContainsOp).arg(A::Other),
+        op(119, Reraise).arg(A::Other),
+        op(121, JumpIfNotExcMatch).jump(J::Absolute),
+        op(122, SetupFinally).jump(J::Forward),
+        op(124, LoadFast).arg(A::Local),
+        op(125, StoreFast).arg(A::Local),
+        op(126, DeleteFast).arg(A::Local),
+        op(129, GenStart).arg(A::Other),
+        op(130, RaiseVarargs).arg(A::Other),
+        op(131, CallFunction).arg(A::Other),
+        op(132, MakeFunction).arg(A::Other),
+        op(133, BuildSlice).arg(A::Other),
+        op(135, LoadClosure).arg(A::Free),
+        op(136, LoadDeref).arg(A::Free),
+        op(137, StoreDeref).arg(A::Free),
+        op(138, DeleteDeref).arg(A::Free),
+        op(141, CallFunctionKw).arg(A::Other),
+        op(142, CallFunctionEx).arg(A::Other),
+        op(143, SetupWith).jump(J::Forward),
+        op(144, ExtendedArg).arg(A::Other),
+        op(145, ListAppend).arg(A::Other),
+        op(146, SetAdd).arg(A::Other),
+        op(147, MapAdd).arg(A::Other),
+        op(148, LoadClassderef).arg(A::Free),
+        op(152, MatchClass).arg(A::Other),
+        op(154, SetupAsyncWith).jump(J::Forward),
+        op(155, FormatValue).arg(A::Other),
+        op(156, BuildConstKeyMap).arg(A::Other),
+        op(157, BuildString).arg(A::Other),
+        op(160, LoadMethod).arg(A::Name),
+        op(161, CallMethod).arg(A::Other),
+        op(162, ListExtend).arg(A::Other),
+        op(163, SetUpdate).arg(A::Other),
+        op(164, DictMerge).arg(A::Other),
+        op(165, DictUpdate).arg(A::Other),
+    ],
+);
+
+pub(super) static PY311: OpcodeTable = OpcodeTable::new(
+    PythonVersion::V3_11,
+    &[
+        op(0, Cache),
+        op(1, PopTop),
+        op(2, PushNull),
+        op(9, Nop),
+        op(10, UnaryPositive),
+        op(11, UnaryNegative),
+        op(12, UnaryNot),
+        op(15, UnaryInvert),
+        op(25, BinarySubscr).cache(4),
+        op(30, GetLen),
+        op(31, MatchMapping),
+        op(32, MatchSequence),
+        op(33, MatchKeys),
+        op(35, PushExcInfo),
+        op(36, CheckExcMatch),
+        op(37, CheckEgMatch),
+        op(49, WithExceptStart),
+        op(50, GetAiter),
+        op(51, GetAnext),
+        op(52, BeforeAsyncWith),
+        op(53, BeforeWith),
+        op(54, EndAsyncFor),
+        op(60, StoreSubscr).cache(1),
+        op(61, DeleteSubscr),
+        op(68, GetIter),
+        op(69, GetYieldFromIter),
+        op(70, PrintExpr),
+        op(71, LoadBuildClass),
+        op(74, LoadAssertionError),
+        op(75, ReturnGenerator),
+        op(82, ListToTuple),
+        op(83, ReturnValue),
+        op(84, ImportStar),
+        op(85, SetupAnnotations),
+        op(86, YieldValue),
+        op(87, AsyncGenWrap),
+        op(88, PrepReraiseStar),
+        op(89, PopExcept),
+        op(90, StoreName).arg(A::Name),
+        op(91, DeleteName).arg(A::Name),
+        op(92, UnpackSequence).arg(A::Other).cache(1),
+        op(93, ForIter).jump(J::Forward),
+        op(94, UnpackEx).arg(A::Other),
+        op(95, StoreAttr).arg(A::Name).cache(4),
+        op(96, DeleteAttr).arg(A::Name),
+        op(97, StoreGlobal).arg(A::Name),
+        op(98, DeleteGlobal).arg(A::Name),
+        op(99, Swap).arg(A::Other),
+        op(100, LoadConst).arg(A::Const),
+        op(101, LoadName).arg(A::Name),
+        op(102, BuildTuple).arg(A::Other),
+        op(103, BuildList).arg(A::Other),
+        op(104, BuildSet).arg(A::Other),
+        op(105, BuildMap).arg(A::Other),
+        op(106, LoadAttr).arg(A::Name).cache(4),
+        op(107, CompareOp).arg(A::Compare).cache(2),
+        op(108, ImportName).arg(A::Name),
+        op(109, ImportFrom).arg(A::Name),
+        op(110, JumpForward).jump(J::Forward),
+        op(111, JumpIfFalseOrPop).jump(J::Forward),
+        op(112, JumpIfTrueOrPop).jump(J::Forward),
+        op(114, PopJumpForwardIfFalse).jump(J::Forward),
+        op(115, PopJumpForwardIfTrue).jump(J::Forward),
+        op(116, LoadGlobal).arg(A::Name).cache(5),
+        op(117, IsOp).arg(A::Other),
+        op(118, ContainsOp).arg(A::Other),
+        op(119, Reraise).arg(A::Other),
+        op(120, Copy).arg(A::Other),
+        op(122, BinaryOp).arg(A::Other).cache(1),
+        op(123, Send).jump(J::Forward),
+        op(124, LoadFast).arg(A::Local),
+        op(125, StoreFast).arg(A::Local),
+        op(126, DeleteFast).arg(A::Local),
+        op(128, PopJumpForwardIfNotNone).jump(J::Forward),
+        op(129, PopJumpForwardIfNone).jump(J::Forward),
+        op(130, RaiseVarargs).arg(A::Other),
+        op(131, GetAwaitable).arg(A::Other),
+        op(132, MakeFunction).arg(A::Other),
+        op(133, BuildSlice).arg(A::Other),
+        op(134, JumpBackwardNoInterrupt).jump(J::Backward),
+        op(135, MakeCell).arg(A::Free),
+        op(136, LoadClosure).arg(A::Free),
+        op(137, LoadDeref).arg(A::Free),
+        op(138, StoreDeref).arg(A::Free),
+        op(139, DeleteDeref).arg(A::Free),
+        op(140, JumpBackward).jump(J::Backward),
+        op(142, CallFunctionEx).arg(A::Other),
+        op(144, ExtendedArg).arg(A::Other),
+        op(145, ListAppend).arg(A::Other),
+        op(146, SetAdd).arg(A::Other),
+        op(147, MapAdd).arg(A::Other),
+        op(148, LoadClassderef).arg(A::Free),
+        op(149, CopyFreeVars).arg(A::Other),
+        op(151, Resume).arg(A::Other),
+        op(152, MatchClass).arg(A::Other),
+        op(155, FormatValue).arg(A::Other),
+        op(156, BuildConstKeyMap).arg(A::Other),
+        op(157, BuildString).arg(A::Other),
+        op(160, LoadMethod).arg(A::Name).cache(10),
+        op(162, ListExtend).arg(A::Other),
+        op(163, SetUpdate).arg(A::Other),
+        op(164, DictMerge).arg(A::Other),
+        op(165, DictUpdate).arg(A::Other),
+        op(166, Precall).arg(A::Other).cache(1),
+        op(171, Call).arg(A::Other).cache(4),
+        op(172, KwNames).arg(A::Const),
+        op(173, PopJumpBackwardIfNotNone).jump(J::Backward),
+        op(174, PopJumpBackwardIfNone).jump(J::Backward),
+        op(175, PopJumpBackwardIfFalse).jump(J::Backward),
+        op(176, PopJumpBackwardIfTrue).jump(J::Backward),
+    ],
+);
+
+pub(super) static PY312: OpcodeTable = OpcodeTable::new(
+    PythonVersion::V3_12,
+    &[
+        op(0, Cache),
+        op(1, PopTop),
+        op(2, PushNull),
+        op(3, InterpreterExit),
+        op(4, EndFor),
+        op(5, EndSend),
+        op(9, Nop),
+        op(11, UnaryNegative),
+        op(12, UnaryNot),
+        op(15, UnaryInvert),
+        op(17, Reserved),
+        op(25, BinarySubscr).cache(1),
+        op(26, BinarySlice),
+        op(27, StoreSlice),
+        op(30, GetLen),
+        op(31, MatchMapping),
+        op(32, MatchSequence),
+        op(33, MatchKeys),
+        op(35, PushExcInfo),
+        op(36, CheckExcMatch),
+        op(37, CheckEgMatch),
+        op(49, WithExceptStart),
+        op(50, GetAiter),
+        op(51, GetAnext),
+        op(52, BeforeAsyncWith),
+        op(53, BeforeWith),
+        op(54, EndAsyncFor),
+        op(55, CleanupThrow),
+        op(60, StoreSubscr).cache(1),
+        op(61, DeleteSubscr),
+        op(68, GetIter),
+        op(69, GetYieldFromIter),
+        op(71, LoadBuildClass),
+        op(74, LoadAssertionError),
+        op(75, ReturnGenerator),
+        op(83, ReturnValue),
+        op(85, SetupAnnotations),
+        op(87, LoadLocals),
+        op(89, PopExcept),
+        op(90, StoreName).arg(A::Name),
+        op(91, DeleteName).arg(A::Name),
+        op(92, UnpackSequence).arg(A::Other).cache(1),
+        op(93, ForIter).jump(J::Forward).cache(1),
+        op(94, UnpackEx).arg(A::Other),
+        op(95, StoreAttr).arg(A::Name).cache(4),
+        op(96, DeleteAttr).arg(A::Name),
+        op(97, StoreGlobal).arg(A::Name),
+        op(98, DeleteGlobal).arg(A::Name),
+        op(99, Swap).arg(A::Other),
+        op(100, LoadConst).arg(A::Const),
+        op(101, LoadName).arg(A::Name),
+        op(102, BuildTuple).arg(A::Other),
+        op(103, BuildList).arg(A::Other),
+        op(104, BuildSet).arg(A::Other),
+        op(105, BuildMap).arg(A::Other),
+        op(106, LoadAttr).arg(A::Name).cache(9),
+        op(107, CompareOp).arg(A::Compare).cache(1),
+        op(108, ImportName).arg(A::Name),
+        op(109, ImportFrom).arg(A::Name),
+        op(110, JumpForward).jump(J::Forward),
+        op(114, PopJumpIfFalse).jump(J::Forward),
+        op(115, PopJumpIfTrue).jump(J::Forward),
+        op(116, LoadGlobal).arg(A::Name).cache(4),
+        op(117, IsOp).arg(A::Other),
+        op(118, ContainsOp).arg(A::Other),
+        op(119, Reraise).arg(A::Other),
+        op(120, Copy).arg(A::Other),
+        op(121, ReturnConst).arg(A::Const),
+        op(122, BinaryOp).arg(A::Other).cache(1),
+        op(123, Send).jump(J::Forward).cache(1),
+        op(124, LoadFast).arg(A::Local),
+        op(125, StoreFast).arg(A::Local),
+        op(126, DeleteFast).arg(A::Local),
+        op(127, LoadFastCheck).arg(A::Local),
+        op(128, PopJumpIfNotNone).jump(J::Forward),
+        op(129, PopJumpIfNone).jump(J::Forward),
+        op(130, RaiseVarargs).arg(A::Other),
+        op(131, GetAwaitable).arg(A::Other),
+        op(132, MakeFunction).arg(A::Other),
+        op(133, BuildSlice).arg(A::Other),
+        op(134, JumpBackwardNoInterrupt).jump(J::Backward),
+        op(135, MakeCell).arg(A::Free),
+        op(136, LoadClosure).arg(A::Free),
+        op(137, LoadDeref).arg(A::Free),
+        op(138, StoreDeref).arg(A::Free),
+        op(139, DeleteDeref).arg(A::Free),
+        op(140, JumpBackward).jump(J::Backward),
+        op(141, LoadSuperAttr).arg(A::Name).cache(1),
+        op(142, CallFunctionEx).arg(A::Other),
+        op(143, LoadFastAndClear).arg(A::Local),
+        op(144, ExtendedArg).arg(A::Other),
+        op(145, ListAppend).arg(A::Other),
+        op(146, SetAdd).arg(A::Other),
+        op(147, MapAdd).arg(A::Other),
+        op(149, CopyFreeVars).arg(A::Other),
+        op(150, YieldValue).arg(A::Other),
+        op(151, Resume).arg(A::Other),
+        op(152, MatchClass).arg(A::Other),
+        op(155, FormatValue).arg(A::Other),
+        op(156, BuildConstKeyMap).arg(A::Other),
+        op(157, BuildString).arg(A::Other),
+        op(162, ListExtend).arg(A::Other),
+        op(163, SetUpdate).arg(A::Other),
+        op(164, DictMerge).arg(A::Other),
+        op(165, DictUpdate).arg(A::Other),
+        op(171, Call).arg(A::Other).cache(3),
+        op(172, KwNames).arg(A::Const),
+        op(173, CallIntrinsic1).arg(A::Other),
+        op(174, CallIntrinsic2).arg(A::Other),
+        op(175, LoadFromDictOrGlobals).arg(A::Name),
+        op(176, LoadFromDictOrDeref).arg(A::Free),
+    ],
+);
+
+pub(super) static PY313: OpcodeTable = OpcodeTable::new(
+    PythonVersion::V3_13,
+    &[
+        op(0, Cache),
+        op(1, BeforeAsyncWith),
+        op(2, BeforeWith),
+        op(4, BinarySlice),
+        op(5, BinarySubscr).cache(1),
+        op(6, CheckEgMatch),
+        op(7, CheckExcMatch),
+        op(8, CleanupThrow),
+        op(9, DeleteSubscr),
+        op(10, EndAsyncFor),
+        op(11, EndFor),
+        op(12, EndSend),
+        op(13, ExitInitCheck),
+        op(14, FormatSimple),
+        op(15, FormatWithSpec),
+        op(16, GetAiter),
+        op(17, Reserved),
+        op(18, GetAnext),
+        op(19, GetIter),
+        op(20, GetLen),
+        op(21, GetYieldFromIter),
+        op(22, InterpreterExit),
+        op(23, LoadAssertionError),
+        op(24, LoadBuildClass),
+        op(25, LoadLocals),
+        op(26, MakeFunction),
+        op(27, MatchKeys),
+        op(28, MatchMapping),
+        op(29, MatchSequence),
+        op(30, Nop),
+        op(31, PopExcept),
+        op(32, PopTop),
+        op(33, PushExcInfo),
+        op(34, PushNull),
+        op(35, ReturnGenerator),
+        op(36, ReturnValue),
+        op(37, SetupAnnotations),
+        op(38, StoreSlice),
+        op(39, StoreSubscr).cache(1),
+        op(40, ToBool).cache(3),
+        op(41, UnaryInvert),
+        op(42, UnaryNegative),
+        op(43, UnaryNot),
+        op(44, WithExceptStart),
+        op(45, BinaryOp).arg(A::Other).cache(1),
+        op(46, BuildConstKeyMap).arg(A::Other),
+        op(47, BuildList).arg(A::Other),
+        op(48, BuildMap).arg(A::Other),
+        op(49, BuildSet).arg(A::Other),
+        op(50, BuildSlice).arg(A::Other),
+        op(51, BuildString).arg(A::Other),
+        op(52, BuildTuple).arg(A::Other),
+        op(53, Call).arg(A::Other).cache(3),
+        op(54, CallFunctionEx).arg(A::Other),
+        op(55, CallIntrinsic1).arg(A::Other),
+        op(56, CallIntrinsic2).arg(A::Other),
+        op(57, CallKw).arg(A::Other),
+        op(58, CompareOp).arg(A::Compare).cache(1),
+        op(59, ContainsOp).arg(A::Other).cache(1),
+        op(60, ConvertValue).arg(A::Other),
+        op(61, Copy).arg(A::Other),
+        op(62, CopyFreeVars).arg(A::Other),
+        op(63, DeleteAttr).arg(A::Name),
+        op(64, DeleteDeref).arg(A::Free),
+        op(65, DeleteFast).arg(A::Local),
+        op(66, DeleteGlobal).arg(A::Name),
+        op(67, DeleteName).arg(A::Name),
+        op(68, DictMerge).arg(A::Other),
+        op(69, DictUpdate).arg(A::Other),
+        op(71, ExtendedArg).arg(A::Other),
+        op(72, ForIter).jump(J::Forward).cache(1),
+        op(73, GetAwaitable).arg(A::Other),
+        op(74, ImportFrom).arg(A::Name),
+        op(75, ImportName).arg(A::Name),
+        op(76, IsOp).arg(A::Other),
+        op(77, JumpBackward).jump(J::Backward).cache(1),
+        op(78, JumpBackwardNoInterrupt).jump(J::Backward),
+        op(79, JumpForward).jump(J::Forward),
+        op(80, ListAppend).arg(A::Other),
+        op(81, ListExtend).arg(A::Other),
+        op(82, LoadAttr).arg(A::Name).cache(9),
+        op(83, LoadConst).arg(A::Const),
+        op(84, LoadDeref).arg(A::Free),
+        op(85, LoadFast).arg(A::Local),
+        op(86, LoadFastAndClear).arg(A::Local),
+        op(87, LoadFastCheck).arg(A::Local),
+        op(88, LoadFastLoadFast).arg(A::Local),
+        op(89, LoadFromDictOrDeref).arg(A::Free),
+        op(90, LoadFromDictOrGlobals).arg(A::Name),
+        op(91, LoadGlobal).arg(A::Name).cache(4),
+        op(92, LoadName).arg(A::Name),
+        op(93, LoadSuperAttr).arg(A::Name).cache(1),
+        op(94, MakeCell).arg(A::Free),
+        op(95, MapAdd).arg(A::Other),
+        op(96, MatchClass).arg(A::Other),
+        op(97, PopJumpIfFalse).jump(J::Forward).cache(1),
+        op(98, PopJumpIfNone).jump(J::Forward).cache(1),
+        op(99, PopJumpIfNotNone).jump(J::Forward).cache(1),
+        op(100, PopJumpIfTrue).jump(J::Forward).cache(1),
+        op(101, RaiseVarargs).arg(A::Other),
+        op(102, Reraise).arg(A::Other),
+        op(103, ReturnConst).arg(A::Const),
+        op(104, Send).jump(J::Forward).cache(1),
+        op(105, SetAdd).arg(A::Other),
+        op(106, SetFunctionAttribute).arg(A::Other),
+        op(107, SetUpdate).arg(A::Other),
+        op(108, StoreAttr).arg(A::Name).cache(4),
+        op(109, StoreDeref).arg(A::Free),
+        op(110, StoreFast).arg(A::Local),
+        op(111, StoreFastLoadFast).arg(A::Local),
+        op(112, StoreFastStoreFast).arg(A::Local),
+        op(113, StoreGlobal).arg(A::Name),
+        op(114, StoreName).arg(A::Name),
+        op(115, Swap).arg(A::Other),
+        op(116, UnpackEx).arg(A::Other),
+        op(117, UnpackSequence).arg(A::Other).cache(1),
+        op(118, YieldValue).arg(A::Other),
+        op(149, Resume).arg(A::Other),
+    ],
+);
+
+pub(super) static PY314: OpcodeTable = OpcodeTable::new(
+    PythonVersion::V3_14,
+    &[
+        op(0, Cache),
+        op(1, BinarySlice),
+        op(2, BuildTemplate),
+        op(4, CallFunctionEx),
+        op(5, CheckEgMatch),
+        op(6, CheckExcMatch),
+        op(7, CleanupThrow),
+        op(8, DeleteSubscr),
+        op(9, EndFor),
+        op(10, EndSend),
+        op(11, ExitInitCheck),
+        op(12, FormatSimple),
+        op(13, FormatWithSpec),
+        op(14, GetAiter),
+        op(15, GetAnext),
+        op(16, GetIter),
+        op(17, Reserved),
+        op(18, GetLen),
+        op(19, GetYieldFromIter),
+        op(20, InterpreterExit),
+        op(21, LoadBuildClass),
+        op(22, LoadLocals),
+        op(23, MakeFunction),
+        op(24, MatchKeys),
+        op(25, MatchMapping),
+        op(26, MatchSequence),
+        op(27, Nop),
+        op(28, NotTaken),
+        op(29, PopExcept),
+        op(30, PopIter),
+        op(31, PopTop),
+        op(32, PushExcInfo),
+        op(33, PushNull),
+        op(34, ReturnGenerator),
+        op(35, ReturnValue),
+        op(36, SetupAnnotations),
+        op(37, StoreSlice),
+        op(38, StoreSubscr).cache(1),
+        op(39, ToBool).cache(3),
+        op(40, UnaryInvert),
+        op(41, UnaryNegative),
+        op(42, UnaryNot),
+        op(43, WithExceptStart),
+        op(44, BinaryOp).arg(A::Other).cache(5),
+        op(45, BuildInterpolation).arg(A::Other),
+        op(46, BuildList).arg(A::Other),
+        op(47, BuildMap).arg(A::Other),
+        op(48, BuildSet).arg(A::Other),
+        op(49, BuildSlice).arg(A::Other),
+        op(50, BuildString).arg(A::Other),
+        op(51, BuildTuple).arg(A::Other),
+        op(52, Call).arg(A::Other).cache(3),
+        op(53, CallIntrinsic1).arg(A::Other),
+        op(54, CallIntrinsic2).arg(A::Other),
+        op(55, CallKw).arg(A::Other).cache(3),
+        op(56, CompareOp).arg(A::Compare).cache(1),
+        op(57, ContainsOp).arg(A::Other).cache(1),
+        op(58, ConvertValue).arg(A::Other),
+        op(59, Copy).arg(A::Other),
+        op(60, CopyFreeVars).arg(A::Other),
+        op(61, DeleteAttr).arg(A::Name),
+        op(62, DeleteDeref).arg(A::Free),
+        op(63, DeleteFast).arg(A::Local),
+        op(64, DeleteGlobal).arg(A::Name),
+        op(65, DeleteName).arg(A::Name),
+        op(66, DictMerge).arg(A::Other),
+        op(67, DictUpdate).arg(A::Other),
+        op(68, EndAsyncFor).jump(J::Backward),
+        op(69, ExtendedArg).arg(A::Other),
+        op(70, ForIter).jump(J::Forward).cache(1),
+        op(71, GetAwaitable).arg(A::Other),
+        op(72, ImportFrom).arg(A::Name),
+        op(73, ImportName).arg(A::Name),
+        op(74, IsOp).arg(A::Other),
+        op(75, JumpBackward).jump(J::Backward).cache(1),
+        op(76, JumpBackwardNoInterrupt).jump(J::Backward),
+        op(77, JumpForward).jump(J::Forward),
+        op(78, ListAppend).arg(A::Other),
+        op(79, ListExtend).arg(A::Other),
+        op(80, LoadAttr).arg(A::Name).cache(9),
+        op(81, LoadCommonConstant).arg(A::Other),
+        op(82, LoadConst).arg(A::Const),
+        op(83, LoadDeref).arg(A::Free),
+        op(84, LoadFast).arg(A::Local),
+        op(85, LoadFastAndClear).arg(A::Local),
+        op(86, LoadFastBorrow).arg(A::Local),
+        op(87, LoadFastBorrowLoadFastBorrow).arg(A::Local),
+        op(88, LoadFastCheck).arg(A::Local),
+        op(89, LoadFastLoadFast).arg(A::Local),
+        op(90, LoadFromDictOrDeref).arg(A::Free),
+        op(91, LoadFromDictOrGlobals).arg(A::Name),
+        op(92, LoadGlobal).arg(A::Name).cache(4),
+        op(93, LoadName).arg(A::Name),
+        op(94, LoadSmallInt).arg(A::Other),
+        op(95, LoadSpecial).arg(A::Other),
+        op(96, LoadSuperAttr).arg(A::Name).cache(1),
+        op(97, MakeCell).arg(A::Free),
+        op(98, MapAdd).arg(A::Other),
+        op(99, MatchClass).arg(A::Other),
+        op(100, PopJumpIfFalse).jump(J::Forward).cache(1),
+        op(101, PopJumpIfNone).jump(J::Forward).cache(1),
+        op(102, PopJumpIfNotNone).jump(J::Forward).cache(1),
+        op(103, PopJumpIfTrue).jump(J::Forward).cache(1),
+        op(104, RaiseVarargs).arg(A::Other),
+        op(105, Reraise).arg(A::Other),
+        op(106, Send).jump(J::Forward).cache(1),
+        op(107, SetAdd).arg(A::Other),
+        op(108, SetFunctionAttribute).arg(A::Other),
+        op(109, SetUpdate).arg(A::Other),
+        op(110, StoreAttr).arg(A::Name).cache(4),
+        op(111, StoreDeref).arg(A::Free),
+        op(112, StoreFast).arg(A::Local),
+        op(113, StoreFastLoadFast).arg(A::Local),
+        op(114, StoreFastStoreFast).arg(A::Local),
+        op(115, StoreGlobal).arg(A::Name),
+        op(116, StoreName).arg(A::Name),
+        op(117, Swap).arg(A::Other),
+        op(118, UnpackEx).arg(A::Other),
+        op(119, UnpackSequence).arg(A::Other).cache(1),
+        op(120, YieldValue).arg(A::Other),
+        op(128, Resume).arg(A::Other),
+    ],
+);
@@ -4,29 +4,243 @@
 //! This module is intended to abstract over format differences for further
 //! analysis, and in particular is intended to be abstractly interpreted
 
+use crate::opcode::Opcode;
+
 pub mod parse;
+pub use parse::parse;
+
+/// The IR of a code object, along with its exception table and where each
+/// instruction came from
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParsedCode {
+    pub instructions: Vec<Instruction>,
+    pub exception_handlers: Vec<ExceptionHandler>,
+    // The origin of the instruction at the same index
+    pub origins: Vec<Origin>,
+}
+
+/// The opcode an instruction was lowered from, which is shared by every
+/// instruction lowered from it. The offset is in bytes, like the offsets of
+/// `dis` and the line table, and is of the opcode itself rather than any
+/// `EXTENDED_ARG`s before it
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Origin {
+    pub offset: usize,
+    pub opcode: Opcode,
+}
+
+/// An entry of the exception table, with indices into the instructions
+/// instead of byte offsets. Exceptions raised by the instructions in
+/// `start..end` jump to target, with the stack popped down to depth items
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExceptionHandler {
+    pub start: u32,
+    pub end: u32,
+    pub target: u32,
+    pub depth: u32,
+    // The offset of the instruction that raised is pushed below the exception
+    pub lasti: bool,
+}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Instruction {
     LoadConst(Constant),
-    Load { from: UnresolvedPlace },
-    Store { into: UnresolvedPlace },
+    Load {
+        from: UnresolvedPlace,
+    },
+    Store {
+        into: UnresolvedPlace,
+    },
+    // `del place`
+    Delete {
+        place: DeletePlace,
+    },
+    // Pops a mapping and loads from it, falling back to the place if it isn't
+    // in there. The mapping is the namespace of a class body, looked up first
+    LoadFromDictOr {
+        from: UnresolvedPlace,
+    },
+    // The namespace of a class body or module
+    LoadLocals,
+    // The `__build_class__` builtin, which class statements call with the
+    // function of the class body, the name and the bases
+    LoadBuildClass,
+    // Name is an index into the names of the code object. If method is set,
+    // NULL is pushed after the attribute, as the receiver for a call
+    LoadAttr {
+        name: u32,
+        method: bool,
+    },
+    StoreAttr {
+        name: u32,
+    },
+    // Pops `super`, the class and self, and loads the attribute from the
+    // result of calling `super` with the class and self
+    LoadSuperAttr {
+        name: u32,
+        method: bool,
+    },
+    // `container[key] = value`, with key on top of the stack
+    StoreSubscript,
+    // Pops count items, or count key value pairs for dicts, and builds them
+    // into a new object
+    Build {
+        kind: BuildKind,
+        count: u32,
+    },
+    // Pops a constant tuple of count keys and the count values below it, and
+    // builds a dict of them
+    BuildConstKeyMap {
+        count: u32,
+    },
+    // Pops an item (or a key and value) and adds it to the container that is
+    // then depth items down the stack, like `LIST_APPEND`
+    ContainerAdd {
+        kind: BuildKind,
+        depth: u32,
+    },
+    // Like ContainerAdd, but adds all the items of an iterable or mapping
+    ContainerExtend {
+        kind: BuildKind,
+        depth: u32,
+    },
+    // Pops a sequence and pushes its count items, the first on top. If star is
+    // set, the item at that index is a list of whatever is left over
+    Unpack {
+        count: u32,
+        star: Option<u32>,
+    },
+    // Pops the level and fromlist and imports the module with this name
+    ImportName {
+        name: u32,
+    },
+    // Loads the name from the module on top of the stack, leaving the module
+    ImportFrom {
+        name: u32,
+    },
+    // Makes the `__annotations__` dict of a module or class body, if it doesn't
+    // have one already
+    SetupAnnotations,
     Pop,
+    // Drops the iterator of a loop
+    PopIter,
     Copy(u32),
     Swap(u32),
     UnaryOp(UnaryOp),
     // Binary OP + Compare OP
     BinaryOp(BinOp),
     // Target is an absolute jump target
-    Jump { class: JumpClass, target: u32 },
-    Call(u32),
+    Jump {
+        class: JumpClass,
+        target: u32,
+    },
+    // Pushes the next item of the iterator on top of the stack, or jumps to
+    // exit_target when it is exhausted, leaving the iterator on the stack
+    ForIter {
+        exit_target: u32,
+    },
+    // Calls the callable below count arguments. If receiver is set there is
+    // also NULL or self next to the callable, on either side of it depending on
+    // the version. If keywords is set, a tuple of keyword names is pushed after
+    // the arguments, naming the last of them
+    Call {
+        count: u32,
+        receiver: bool,
+        keywords: bool,
+    },
+    // `f(*args, **kwargs)`, where args is a tuple and kwargs is a dict, or NULL
+    // if there aren't any keyword arguments
+    CallEx {
+        receiver: bool,
+        kwargs: bool,
+    },
+    CallIntrinsic1(Intrinsic1),
+    CallIntrinsic2(Intrinsic2),
+    // Pops a value and pushes the value sent when the generator is resumed
+    Yield,
+    // Delegates to the iterator or awaitable below the value on top of the
+    // stack (which is sent to it first) until it's exhausted, replacing the
+    // value with the result. This is the loop from `SEND` to `END_SEND`
+    YieldFrom,
+    // Replaces the receiver, last sent value and exception of a `yield from`
+    // or `await` that was thrown into with the receiver and result
+    CleanupThrow,
+    // Pops the value and the receiver below it, and pushes the value back
+    EndSend,
+    // Re-raises the exception on top of the stack
+    Reraise,
+    // Raises with count arguments popped from the stack: none to re-raise
+    // the exception being handled, the exception, or the exception and cause
+    Raise {
+        count: u32,
+    },
+    // Pops a class and pushes whether the exception below it is an instance
+    CheckExcMatch,
+    // Pops a class and an exception group, and pushes the part of the group
+    // that doesn't match the class, followed by the part that does
+    CheckEgMatch,
+    // Pushes the exception being handled below the one on top of the stack,
+    // which becomes the exception being handled
+    PushExcInfo,
+    // Pops the exception pushed below by PushExcInfo, which is handled again
+    PopExcept,
+    // Loads a special method of the object on top of the stack, with self (or
+    // NULL) pushed after it like a method load
+    LoadSpecial(SpecialMethod),
+    // Calls the `__exit__` of a with statement with the exception on top of
+    // the stack, where the exit method is exit_depth items down. From 3.14 it
+    // is 5, with self below the exception, and before that it is 4
+    WithExceptStart {
+        exit_depth: u32,
+    },
     Return,
     MakeFunction,
+    // Pops a function and an attribute, sets it and pushes the function back
+    SetFunctionAttribute(FunctionAttribute),
     // Implicit conversions
     Coercion(Coercion),
+    // Pattern matching, which leaves the subject on the stack except for
+    // MatchClass. Pops the subject, class and a tuple of keyword attribute
+    // names, and pushes a tuple of the count positional attributes followed
+    // by the keyword ones, or None if it doesn't match
+    MatchClass {
+        count: u32,
+    },
+    // Pushes whether the subject is a mapping or a sequence
+    MatchMapping,
+    MatchSequence,
+    // Pushes a tuple of the values of the keys on top of the stack in the
+    // mapping below them, or None if any are missing
+    MatchKeys,
+    // Pushes the length of the top of the stack
+    GetLen,
+    // Applies `!s`, `!r` or `!a` to the value of an f-string field
+    ConvertValue(Conversion),
+    // Formats the value of an f-string field, with the format spec on top of
+    // it if spec is set
+    FormatValue {
+        spec: bool,
+    },
+    // Pops a value, the source text of its expression and the format spec if
+    // spec is set, and pushes a t-string interpolation
+    BuildInterpolation {
+        conversion: Option<Conversion>,
+        spec: bool,
+    },
+    // Pops a tuple of strings and a tuple of interpolations, and pushes a
+    // t-string template
+    BuildTemplate,
 }
 
-#[derive(Debug, Clone, Copy)]
+// Locals and cells are indices into the fast locals of the code object, and
+// globals and names are indices into its names. Before 3.11 cells are indices
+// into the cell and free variables instead
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnresolvedPlace {
     Global(u32),
     Local(u32),
@@ -34,22 +248,128 @@ pub enum UnresolvedPlace {
     Name(u32),
 }
 
-#[derive(Debug, Clone, Copy)]
+// The places `del` unbinds. Attributes pop the object, and subscripts pop the
+// container and the key on top of it
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeletePlace {
+    Variable(UnresolvedPlace),
+    Attribute(u32),
+    Subscript,
+}
+
+// The attributes set by `SET_FUNCTION_ATTRIBUTE`, in the order of the bits of
+// its argument
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FunctionAttribute {
+    // A tuple of the default values of the last positional parameters
+    Defaults,
+    // A dict of the default values of keyword only parameters
+    KwDefaults,
+    // A tuple of parameter names and annotations, flattened, before 3.14
+    Annotations,
+    // A tuple of the cells of the free variables of the function
+    Closure,
+    // The function that evaluates the annotations lazily, since 3.14
+    Annotate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Constant {
     ByIndex(u32),
     SmallInt(u8),
     None,
     Null,
+    Common(CommonConstant),
+}
+
+// The objects loaded by `LOAD_COMMON_CONSTANT`, in the order of its argument.
+// Before 3.14 `LOAD_ASSERTION_ERROR` loads the first one
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CommonConstant {
+    AssertionError,
+    NotImplementedError,
+    // The builtin `tuple`, `all` and `any`
+    Tuple,
+    All,
+    Any,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BuildKind {
+    Tuple,
+    List,
+    Set,
+    Dict,
+    // Two or three items, the step being optional
+    Slice,
+    // Concatenation of the formatted pieces of an f-string
+    String,
+}
+
+// The internal functions called by `CALL_INTRINSIC_1`, in the order of their
+// arguments. Unary positive is lowered to a [`UnaryOp`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Intrinsic1 {
+    Print,
+    ImportStar,
+    StopIterationError,
+    AsyncGenWrap,
+    ListToTuple,
+    TypeVar,
+    ParamSpec,
+    TypeVarTuple,
+    SubscriptGeneric,
+    TypeAlias,
+}
+
+// The internal functions called by `CALL_INTRINSIC_2`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Intrinsic2 {
+    PrepReraiseStar,
+    TypeVarWithBound,
+    TypeVarWithConstraints,
+    SetFunctionTypeParams,
+    SetTypeParamDefault,
+}
+
+// The methods loaded by `LOAD_SPECIAL`, in the order of its argument
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpecialMethod {
+    Enter,
+    Exit,
+    AsyncEnter,
+    AsyncExit,
+}
+
+// The conversions of f-string fields, in the order of their arguments
+// starting from 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Conversion {
+    Str,
+    Repr,
+    Ascii,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnaryOp {
+    Positive,
     Negative,
     LogicalNot,
     Invert,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinOp {
     Add,
     Sub,
@@ -85,18 +405,26 @@ pub enum BinOp {
     GtEq,
     LtEq,
     Is,
+    IsNot,
+    In,
+    NotIn,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JumpClass {
     Always,
     IfFalse,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Coercion {
     Bool,
     Iter,
     Awaitable,
     AsyncIter,
+    // The awaitable for the next item of an async iterator
+    AsyncNext,
+    YieldFromIter,
 }
@@ -1,73 +1,180 @@
-use crate::stack_ir::{BinOp, Coercion, JumpClass, UnaryOp, UnresolvedPlace};
+use crate::{
+    opcode::{DecodeError, Opcode, decode, parse_exception_table},
+    stack_ir::{
+        BinOp, BuildKind, Coercion, Conversion, DeletePlace, FunctionAttribute, Intrinsic1,
+        Intrinsic2, JumpClass, SpecialMethod, UnaryOp, UnresolvedPlace,
+    },
+    version::PythonVersion,
+};
 
-use super::{Constant, Instruction};
+use super::{CommonConstant, Constant, ExceptionHandler, Instruction, Origin, ParsedCode};
 
 #[derive(Debug)]
 pub enum IRParseError {
     SmallIntTooLarge(u32),
     OutOfBoundsBinOp(u32),
     OutOfBoundsCompareOp(u32),
-    ArgExtendWouldOverflow(u32),
-    NotYetImplementedInstruction(u8),
+    Decode(DecodeError),
+    NotYetImplementedInstruction(Opcode),
     JumpPastEnd(u32),
-    JumpBeforeStart(u32),
+    UnexpectedCache(usize),
+    OutOfBoundsIntrinsic(u32),
+    OutOfBoundsSpecialMethod(u32),
+    OutOfBoundsCommonConstant(u32),
+    OutOfBoundsConversion(u32),
+    // Only one attribute is set at a time since 3.13
+    OutOfBoundsFunctionAttribute(u32),
+    // The handler of an exception table entry isn't an instruction
+    HandlerPastEnd(usize),
+    // Before 3.11 the qualified name is loaded just before MAKE_FUNCTION,
+    // which is expected to be a constant
+    NonConstantQualname(usize),
 }
 
-pub fn parse314(code: &[u8]) -> Result<Vec<Instruction>, IRParseError> {
-    let code = as_tuple(code);
-    let mut out = Vec::new();
-    let mut mapping = Vec::new();
-
-    let mut arg_extension = 0u32;
-    macro_rules! extend_arg {
-        ($base:expr) => {{
-            let _intermediate = ($base as u32) + arg_extension;
-            arg_extension = 0;
-            _intermediate
-        }};
+/// Lower bytecode with the front end for its version. The exception table is
+/// ignored before 3.11, where there isn't one
+pub fn parse(
+    version: PythonVersion,
+    code: &[u8],
+    exception_table: &[u8],
+) -> Result<ParsedCode, IRParseError> {
+    match version {
+        PythonVersion::V3_8 => parse38(code),
+        PythonVersion::V3_9 => parse39(code),
+        PythonVersion::V3_10 => parse310(code),
+        PythonVersion::V3_11 => parse311(code, exception_table),
+        PythonVersion::V3_12 => parse312(code, exception_table),
+        PythonVersion::V3_13 => parse313(code, exception_table),
+        PythonVersion::V3_14 => parse314(code, exception_table),
     }
+}
 
-    let mut instruction_count = 0;
-    macro_rules! push {
-        ($val:expr) => {{
-            out.push($val);
-            mapping.push(instruction_count);
-        }};
-    }
+// Before 3.11 exceptions are handled with blocks that are set up and popped by
+// instructions rather than an exception table, which isn't lowered yet
+
+pub fn parse38(code: &[u8]) -> Result<ParsedCode, IRParseError> {
+    lower(PythonVersion::V3_8, code, &[])
+}
+
+pub fn parse39(code: &[u8]) -> Result<ParsedCode, IRParseError> {
+    lower(PythonVersion::V3_9, code, &[])
+}
+
+pub fn parse310(code: &[u8]) -> Result<ParsedCode, IRParseError> {
+    lower(PythonVersion::V3_10, code, &[])
+}
+
+pub fn parse311(code: &[u8], exception_table: &[u8]) -> Result<ParsedCode, IRParseError> {
+    lower(PythonVersion::V3_11, code, exception_table)
+}
+
+pub fn parse312(code: &[u8], exception_table: &[u8]) -> Result<ParsedCode, IRParseError> {
+    lower(PythonVersion::V3_12, code, exception_table)
+}
+
+pub fn parse313(code: &[u8], exception_table: &[u8]) -> Result<ParsedCode, IRParseError> {
+    lower(PythonVersion::V3_13, code, exception_table)
+}
+
+pub fn parse314(code: &[u8], exception_table: &[u8]) -> Result<ParsedCode, IRParseError> {
+    lower(PythonVersion::V3_14, code, exception_table)
+}
+
+/// Lower the bytecode of any version into the IR. Most opcodes mean the same
+/// thing in every version they exist in, and the ones whose arguments or
+/// stack effects changed are matched on the version
+fn lower(
+    version: PythonVersion,
+    code: &[u8],
+    exception_table: &[u8],
+) -> Result<ParsedCode, IRParseError> {
+    let mut out = Vec::new();
+    let mut origins = Vec::new();
+    let code_units = (code.len() / 2) as u32;
+    // FOR_ITERs that pop the iterator when it's exhausted, before 3.14
+    let mut popping_for_iters = Vec::new();
+    // KW_NAMES sets the keyword names of the next call in 3.11 and 3.12
+    let mut kw_names = false;
+
+    let mut previous = None;
+    for raw in decode(version, code) {
+        let raw = raw.map_err(IRParseError::Decode)?;
+        let previous = previous.replace(raw.opcode);
+        let origin = Origin {
+            offset: raw.offset,
+            opcode: raw.opcode,
+        };
+        let arg = raw.arg;
+        // Jump targets are in code units until they're patched below
+        let jump_target = || {
+            let Some(target) = raw.jump_target else {
+                unreachable!("The decoder gives every jump opcode a target")
+            };
+            let target = (target / 2) as u32;
+            match target < code_units {
+                true => Ok(target),
+                false => Err(IRParseError::JumpPastEnd(target)),
+            }
+        };
+        macro_rules! push {
+            ($val:expr) => {{
+                out.push($val);
+                origins.push(origin);
+            }};
+        }
 
-    for operation in code {
-        // TODO: Remove all the magic numbers (and in general make this easier
-        // to generalise to all python versions).
-        match operation {
+        match raw.opcode {
             // Load consts
-            (82, idx) => {
-                arg_extension = 0;
-                push!(Instruction::LoadConst(Constant::ByIndex(extend_arg!(*idx))));
-            }
-            (94, n) => {
-                let n2 = extend_arg!(*n);
-                if n2 > 255 {
-                    return Err(IRParseError::SmallIntTooLarge(n2));
+            Opcode::LoadConst => {
+                push!(Instruction::LoadConst(Constant::ByIndex(arg)));
+            }
+            Opcode::LoadSmallInt => {
+                if arg > 255 {
+                    return Err(IRParseError::SmallIntTooLarge(arg));
                 }
-                push!(Instruction::LoadConst(Constant::SmallInt(*n)))
+                push!(Instruction::LoadConst(Constant::SmallInt(arg as u8)))
+            }
+            Opcode::LoadCommonConstant => {
+                push!(Instruction::LoadConst(Constant::Common(match arg {
+                    0 => CommonConstant::AssertionError,
+                    1 => CommonConstant::NotImplementedError,
+                    2 => CommonConstant::Tuple,
+                    3 => CommonConstant::All,
+                    4 => CommonConstant::Any,
+                    n => return Err(IRParseError::OutOfBoundsCommonConstant(n)),
+                })))
             }
-            (33, _) => {
-                arg_extension = 0;
+            Opcode::LoadAssertionError => push!(Instruction::LoadConst(Constant::Common(
+                CommonConstant::AssertionError
+            ))),
+            Opcode::PushNull => {
                 push!(Instruction::LoadConst(Constant::Null))
             }
 
             // Loads
-            (92, arg) => {
-                push!(Instruction::LoadConst(Constant::Null));
+            Opcode::LoadGlobal if version < PythonVersion::V3_11 => push!(Instruction::Load {
+                from: UnresolvedPlace::Global(arg),
+            }),
+            // NULL is pushed before the global until 3.13
+            Opcode::LoadGlobal => {
+                let null = arg & 1 != 0;
+                if null && version < PythonVersion::V3_13 {
+                    push!(Instruction::LoadConst(Constant::Null));
+                }
                 push!(Instruction::Load {
-                    from: UnresolvedPlace::Global(extend_arg!((*arg) >> 1)),
+                    from: UnresolvedPlace::Global(arg >> 1),
                 });
+                if null && version >= PythonVersion::V3_13 {
+                    push!(Instruction::LoadConst(Constant::Null));
+                }
             }
-            (83 | 84 | 85 | 86 | 88, arg) => push!(Instruction::Load {
-                from: UnresolvedPlace::Local(extend_arg!(*arg)),
+            Opcode::LoadFast
+            | Opcode::LoadFastAndClear
+            | Opcode::LoadFastBorrow
+            | Opcode::LoadFastCheck => push!(Instruction::Load {
+                from: UnresolvedPlace::Local(arg),
             }),
-            (87 | 89, arg) => {
-                let arg = extend_arg!(*arg);
+            Opcode::LoadFastBorrowLoadFastBorrow | Opcode::LoadFastLoadFast => {
                 push!(Instruction::Load {
                     from: UnresolvedPlace::Local(arg >> 4),
                 });
@@ -75,33 +182,185 @@ pub fn parse314(code: &[u8]) -> Result<Vec<Instruction>, IRParseError> {
                     from: UnresolvedPlace::Local(arg & 15),
                 });
             }
-            (93, arg) => push!(Instruction::Load {
-                from: UnresolvedPlace::Name(extend_arg!(*arg))
+            Opcode::LoadName => push!(Instruction::Load {
+                from: UnresolvedPlace::Name(arg)
+            }),
+            // Closures are built from the cells themselves, but there isn't a
+            // difference between a cell and its contents here
+            Opcode::LoadDeref | Opcode::LoadClosure => push!(Instruction::Load {
+                from: UnresolvedPlace::Cell(arg)
+            }),
+            // Looks in the namespace of the class body before the cell
+            Opcode::LoadClassderef => {
+                push!(Instruction::LoadLocals);
+                push!(Instruction::LoadFromDictOr {
+                    from: UnresolvedPlace::Cell(arg)
+                })
+            }
+            Opcode::LoadLocals => push!(Instruction::LoadLocals),
+            Opcode::LoadBuildClass => push!(Instruction::LoadBuildClass),
+            Opcode::LoadFromDictOrDeref => push!(Instruction::LoadFromDictOr {
+                from: UnresolvedPlace::Cell(arg)
+            }),
+            Opcode::LoadFromDictOrGlobals => push!(Instruction::LoadFromDictOr {
+                from: UnresolvedPlace::Global(arg)
             }),
 
             // Stores
-            (112, arg) => push!(Instruction::Store {
-                into: UnresolvedPlace::Local(extend_arg!(*arg)),
+            Opcode::StoreFast => push!(Instruction::Store {
+                into: UnresolvedPlace::Local(arg),
             }),
-            (115, arg) => push!(Instruction::Store {
-                into: UnresolvedPlace::Global(extend_arg!(*arg)),
+            Opcode::StoreGlobal => push!(Instruction::Store {
+                into: UnresolvedPlace::Global(arg),
             }),
-            (114, arg) => {
-                let arg = extend_arg!(*arg);
+            Opcode::StoreFastStoreFast => {
                 push!(Instruction::Store {
                     into: UnresolvedPlace::Local(arg >> 4),
                 });
                 push!(Instruction::Store {
-                    into: UnresolvedPlace::Local(arg * 15),
+                    into: UnresolvedPlace::Local(arg & 15),
                 })
             }
-            (116, arg) => push!(Instruction::Store {
-                into: UnresolvedPlace::Name(extend_arg!(*arg))
+            Opcode::StoreName => push!(Instruction::Store {
+                into: UnresolvedPlace::Name(arg)
+            }),
+            Opcode::StoreDeref => push!(Instruction::Store {
+                into: UnresolvedPlace::Cell(arg)
+            }),
+
+            // Deletes
+            Opcode::DeleteFast => push!(Instruction::Delete {
+                place: DeletePlace::Variable(UnresolvedPlace::Local(arg))
+            }),
+            Opcode::DeleteGlobal => push!(Instruction::Delete {
+                place: DeletePlace::Variable(UnresolvedPlace::Global(arg))
+            }),
+            Opcode::DeleteName => push!(Instruction::Delete {
+                place: DeletePlace::Variable(UnresolvedPlace::Name(arg))
+            }),
+            Opcode::DeleteDeref => push!(Instruction::Delete {
+                place: DeletePlace::Variable(UnresolvedPlace::Cell(arg))
+            }),
+
+            // Imports, star imports are an intrinsic whose result is popped
+            // from 3.12
+            Opcode::ImportName => push!(Instruction::ImportName { name: arg }),
+            Opcode::ImportFrom => push!(Instruction::ImportFrom { name: arg }),
+            Opcode::ImportStar => {
+                push!(Instruction::CallIntrinsic1(Intrinsic1::ImportStar));
+                push!(Instruction::Pop)
+            }
+            Opcode::SetupAnnotations => push!(Instruction::SetupAnnotations),
+
+            // Attributes
+            Opcode::LoadAttr if version < PythonVersion::V3_12 => push!(Instruction::LoadAttr {
+                name: arg,
+                method: false,
+            }),
+            Opcode::LoadAttr => push!(Instruction::LoadAttr {
+                name: arg >> 1,
+                method: arg & 1 != 0,
+            }),
+            Opcode::LoadMethod => push!(Instruction::LoadAttr {
+                name: arg,
+                method: true,
+            }),
+            Opcode::StoreAttr => push!(Instruction::StoreAttr { name: arg }),
+            Opcode::DeleteAttr => push!(Instruction::Delete {
+                place: DeletePlace::Attribute(arg)
+            }),
+            // The second bit is set for two argument super calls, which doesn't
+            // matter since the class and self are on the stack either way
+            Opcode::LoadSuperAttr => push!(Instruction::LoadSuperAttr {
+                name: arg >> 2,
+                method: arg & 1 != 0,
+            }),
+
+            // Subscripts, loads are a binary op
+            Opcode::StoreSubscr => push!(Instruction::StoreSubscript),
+            Opcode::DeleteSubscr => push!(Instruction::Delete {
+                place: DeletePlace::Subscript
+            }),
+            Opcode::BinarySubscr => push!(Instruction::BinaryOp(BinOp::Subscript)),
+            Opcode::BinarySlice => {
+                push!(Instruction::Build {
+                    kind: BuildKind::Slice,
+                    count: 2
+                });
+                push!(Instruction::BinaryOp(BinOp::Subscript))
+            }
+            Opcode::StoreSlice => {
+                push!(Instruction::Build {
+                    kind: BuildKind::Slice,
+                    count: 2
+                });
+                push!(Instruction::StoreSubscript)
+            }
+
+            // Containers
+            Opcode::BuildTuple => push!(Instruction::Build {
+                kind: BuildKind::Tuple,
+                count: arg
+            }),
+            Opcode::BuildList => push!(Instruction::Build {
+                kind: BuildKind::List,
+                count: arg
+            }),
+            Opcode::BuildSet => push!(Instruction::Build {
+                kind: BuildKind::Set,
+                count: arg
+            }),
+            Opcode::BuildMap => push!(Instruction::Build {
+                kind: BuildKind::Dict,
+                count: arg
+            }),
+            Opcode::BuildConstKeyMap => push!(Instruction::BuildConstKeyMap { count: arg }),
+            Opcode::BuildSlice => push!(Instruction::Build {
+                kind: BuildKind::Slice,
+                count: arg
+            }),
+            Opcode::BuildString => push!(Instruction::Build {
+                kind: BuildKind::String,
+                count: arg
+            }),
+            Opcode::ListAppend => push!(Instruction::ContainerAdd {
+                kind: BuildKind::List,
+                depth: arg
+            }),
+            Opcode::SetAdd => push!(Instruction::ContainerAdd {
+                kind: BuildKind::Set,
+                depth: arg
+            }),
+            Opcode::MapAdd => push!(Instruction::ContainerAdd {
+                kind: BuildKind::Dict,
+                depth: arg
+            }),
+            Opcode::ListExtend => push!(Instruction::ContainerExtend {
+                kind: BuildKind::List,
+                depth: arg
+            }),
+            Opcode::SetUpdate => push!(Instruction::ContainerExtend {
+                kind: BuildKind::Set,
+                depth: arg
+            }),
+            // DICT_MERGE only differs in raising on duplicate keys
+            Opcode::DictUpdate | Opcode::DictMerge => push!(Instruction::ContainerExtend {
+                kind: BuildKind::Dict,
+                depth: arg
+            }),
+            Opcode::UnpackSequence => push!(Instruction::Unpack {
+                count: arg,
+                star: None
+            }),
+            // The low byte is the number of items before the starred one, and
+            // the next byte the number after
+            Opcode::UnpackEx => push!(Instruction::Unpack {
+                count: (arg & 0xff) + 1 + (arg >> 8),
+                star: Some(arg & 0xff)
             }),
 
             // Paired load + stores
-            (113, arg) => {
-                let arg = extend_arg!(*arg);
+            Opcode::StoreFastLoadFast => {
                 push!(Instruction::Store {
                     into: UnresolvedPlace::Local(arg >> 4),
                 });
@@ -111,17 +370,46 @@ pub fn parse314(code: &[u8]) -> Result<Vec<Instruction>, IRParseError> {
             }
 
             // Pops
-            (9 | 30 | 31, _) => {
-                arg_extension = 0;
-                push!(Instruction::Pop)
-            }
+            // The generator is created and returned by RETURN_GENERATOR, and
+            // when it's first resumed the value sent (None) is pushed and
+            // popped straight away, so neither is lowered
+            Opcode::PopTop if previous == Some(Opcode::ReturnGenerator) => {}
+            // GEN_START pops that value itself in 3.10
+            Opcode::GenStart => {}
+            // FOR_ITER jumps over END_FOR, and the POP_TOP after it in 3.13,
+            // so they're only executed when instrumented
+            Opcode::PopTop
+                if version == PythonVersion::V3_13 && previous == Some(Opcode::EndFor) => {}
+            Opcode::PopTop => push!(Instruction::Pop),
+            Opcode::PopIter => push!(Instruction::PopIter),
+            // FOR_ITER skips this when the iterator is exhausted, so it's only
+            // executed when instrumented
+            Opcode::EndFor => {}
             // Copy
-            (59, arg) => push!(Instruction::Copy(extend_arg!(*arg))),
+            Opcode::Copy => push!(Instruction::Copy(arg)),
             //Swap
-            (117, arg) => push!(Instruction::Swap(extend_arg!(*arg))),
+            Opcode::Swap => push!(Instruction::Swap(arg)),
+            Opcode::DupTop => push!(Instruction::Copy(1)),
+            Opcode::DupTopTwo => {
+                push!(Instruction::Copy(2));
+                push!(Instruction::Copy(2))
+            }
+            // Rotations move the top of the stack down, which is a series of
+            // swaps from the bottom up
+            Opcode::RotTwo | Opcode::RotThree | Opcode::RotFour | Opcode::RotN => {
+                let n = match raw.opcode {
+                    Opcode::RotTwo => 2,
+                    Opcode::RotThree => 3,
+                    Opcode::RotFour => 4,
+                    _ => arg,
+                };
+                for depth in (2..=n).rev() {
+                    push!(Instruction::Swap(depth))
+                }
+            }
 
             // Binary Ops
-            (44, op) => push!(Instruction::BinaryOp(match extend_arg!(*op) {
+            Opcode::BinaryOp => push!(Instruction::BinaryOp(match arg {
                 0 => BinOp::Add,
                 1 => BinOp::And,
                 2 => BinOp::FloorDiv,
@@ -151,190 +439,882 @@ pub fn parse314(code: &[u8]) -> Result<Vec<Instruction>, IRParseError> {
                 26 => BinOp::Subscript,
                 n => return Err(IRParseError::OutOfBoundsBinOp(n)),
             })),
-            // Comparison Ops
-            (56, arg) => {
-                let arg = extend_arg!(*arg);
-                push!(Instruction::BinaryOp(match arg >> 5 {
-                    0 => BinOp::Lt,
-                    1 => BinOp::LtEq,
-                    2 => BinOp::Eq,
-                    3 => BinOp::Ne,
-                    4 => BinOp::Gt,
-                    5 => BinOp::GtEq,
-                    _ => return Err(IRParseError::OutOfBoundsCompareOp(arg)),
-                }));
-                if arg & 16 != 0 {
+            // Before 3.11 there is an opcode for each binary op
+            Opcode::BinaryAdd => push!(Instruction::BinaryOp(BinOp::Add)),
+            Opcode::BinaryAnd => push!(Instruction::BinaryOp(BinOp::And)),
+            Opcode::BinaryFloorDivide => push!(Instruction::BinaryOp(BinOp::FloorDiv)),
+            Opcode::BinaryLshift => push!(Instruction::BinaryOp(BinOp::LShift)),
+            Opcode::BinaryMatrixMultiply => push!(Instruction::BinaryOp(BinOp::MatMul)),
+            Opcode::BinaryMultiply => push!(Instruction::BinaryOp(BinOp::Mul)),
+            Opcode::BinaryModulo => push!(Instruction::BinaryOp(BinOp::Remainder)),
+            Opcode::BinaryOr => push!(Instruction::BinaryOp(BinOp::Or)),
+            Opcode::BinaryPower => push!(Instruction::BinaryOp(BinOp::Power)),
+            Opcode::BinaryRshift => push!(Instruction::BinaryOp(BinOp::RShift)),
+            Opcode::BinarySubtract => push!(Instruction::BinaryOp(BinOp::Sub)),
+            Opcode::BinaryTrueDivide => push!(Instruction::BinaryOp(BinOp::Div)),
+            Opcode::BinaryXor => push!(Instruction::BinaryOp(BinOp::Xor)),
+            Opcode::InplaceAdd => push!(Instruction::BinaryOp(BinOp::InplaceAdd)),
+            Opcode::InplaceAnd => push!(Instruction::BinaryOp(BinOp::InplaceAnd)),
+            Opcode::InplaceFloorDivide => push!(Instruction::BinaryOp(BinOp::InplaceFloorDiv)),
+            Opcode::InplaceLshift => push!(Instruction::BinaryOp(BinOp::InplaceLShift)),
+            Opcode::InplaceMatrixMultiply => push!(Instruction::BinaryOp(BinOp::InplaceMatMul)),
+            Opcode::InplaceMultiply => push!(Instruction::BinaryOp(BinOp::InplaceMul)),
+            Opcode::InplaceModulo => push!(Instruction::BinaryOp(BinOp::InplaceRemainder)),
+            Opcode::InplaceOr => push!(Instruction::BinaryOp(BinOp::InplaceOr)),
+            Opcode::InplacePower => push!(Instruction::BinaryOp(BinOp::InplacePower)),
+            Opcode::InplaceRshift => push!(Instruction::BinaryOp(BinOp::InplaceRShift)),
+            Opcode::InplaceSubtract => push!(Instruction::BinaryOp(BinOp::InplaceSub)),
+            Opcode::InplaceTrueDivide => push!(Instruction::BinaryOp(BinOp::InplaceDiv)),
+            Opcode::InplaceXor => push!(Instruction::BinaryOp(BinOp::InplaceXor)),
+            // Comparison Ops. The comparison is shifted past a cache mask from
+            // 3.12, and past a bit for coercing the result to a bool from 3.13.
+            // In 3.8 it also covers `in` and `is`
+            Opcode::CompareOp => {
+                let op = match version {
+                    PythonVersion::V3_8 => match arg {
+                        6 => Some(BinOp::In),
+                        7 => Some(BinOp::NotIn),
+                        8 => Some(BinOp::Is),
+                        9 => Some(BinOp::IsNot),
+                        n => comparison(n),
+                    },
+                    _ if version < PythonVersion::V3_12 => comparison(arg),
+                    PythonVersion::V3_12 => comparison(arg >> 4),
+                    _ => comparison(arg >> 5),
+                };
+                let Some(op) = op else {
+                    return Err(IRParseError::OutOfBoundsCompareOp(arg));
+                };
+                push!(Instruction::BinaryOp(op));
+                if version >= PythonVersion::V3_13 && arg & 16 != 0 {
                     push!(Instruction::Coercion(Coercion::Bool));
                 }
             }
-            // Is op
-            (74, _) => {
-                arg_extension = 0;
-                push!(Instruction::BinaryOp(BinOp::Is))
-            }
+            // Is op and contains op, where the argument inverts them
+            Opcode::IsOp => push!(Instruction::BinaryOp(match arg {
+                0 => BinOp::Is,
+                _ => BinOp::IsNot,
+            })),
+            Opcode::ContainsOp => push!(Instruction::BinaryOp(match arg {
+                0 => BinOp::In,
+                _ => BinOp::NotIn,
+            })),
 
-            // Unary Ops
-            (41, _) => {
-                arg_extension = 0;
+            // Unary Ops, positive is an intrinsic from 3.12
+            Opcode::UnaryPositive => push!(Instruction::UnaryOp(UnaryOp::Positive)),
+            Opcode::UnaryNegative => {
                 push!(Instruction::UnaryOp(UnaryOp::Negative))
             }
-            (42, _) => {
-                arg_extension = 0;
+            Opcode::UnaryNot => {
                 push!(Instruction::UnaryOp(UnaryOp::LogicalNot))
             }
-            (40, _) => {
-                arg_extension = 0;
+            Opcode::UnaryInvert => {
                 push!(Instruction::UnaryOp(UnaryOp::Invert))
             }
 
-            // Jumps
-            (100, delta) => {
-                let target = instruction_count + 2 + extend_arg!(*delta);
-                if target as usize >= code.len() {
-                    return Err(IRParseError::JumpPastEnd(target));
-                }
+            // Jumps, which have a direction in their name in 3.11
+            Opcode::PopJumpIfFalse
+            | Opcode::PopJumpForwardIfFalse
+            | Opcode::PopJumpBackwardIfFalse => {
+                let target = jump_target()?;
                 push!(Instruction::Jump {
                     class: JumpClass::IfFalse,
                     target
                 })
             }
-            (101, delta) => {
-                let target = instruction_count + 2 + extend_arg!(*delta);
-                if target as usize >= code.len() {
-                    return Err(IRParseError::JumpPastEnd(target));
-                }
-                out.push(Instruction::LoadConst(Constant::None));
-                out.push(Instruction::BinaryOp(BinOp::Is));
-                out.push(Instruction::UnaryOp(UnaryOp::LogicalNot));
+            Opcode::PopJumpIfNone
+            | Opcode::PopJumpForwardIfNone
+            | Opcode::PopJumpBackwardIfNone => {
+                let target = jump_target()?;
+                push!(Instruction::LoadConst(Constant::None));
+                push!(Instruction::BinaryOp(BinOp::Is));
+                push!(Instruction::UnaryOp(UnaryOp::LogicalNot));
                 push!(Instruction::Jump {
                     class: JumpClass::IfFalse,
                     target
                 })
             }
-            (102, delta) => {
-                let target = instruction_count + 2 + extend_arg!(*delta);
-                if target as usize >= code.len() {
-                    return Err(IRParseError::JumpPastEnd(target));
-                }
-                out.push(Instruction::LoadConst(Constant::None));
-                out.push(Instruction::BinaryOp(BinOp::Is));
+            Opcode::PopJumpIfNotNone
+            | Opcode::PopJumpForwardIfNotNone
+            | Opcode::PopJumpBackwardIfNotNone => {
+                let target = jump_target()?;
+                push!(Instruction::LoadConst(Constant::None));
+                push!(Instruction::BinaryOp(BinOp::Is));
                 push!(Instruction::Jump {
                     class: JumpClass::IfFalse,
                     target
                 })
             }
-            (103, delta) => {
-                let target = instruction_count + 2 + extend_arg!(*delta);
-                if target as usize >= code.len() {
-                    return Err(IRParseError::JumpPastEnd(target));
-                }
-                out.push(Instruction::UnaryOp(UnaryOp::LogicalNot));
+            Opcode::PopJumpIfTrue
+            | Opcode::PopJumpForwardIfTrue
+            | Opcode::PopJumpBackwardIfTrue => {
+                let target = jump_target()?;
+                push!(Instruction::UnaryOp(UnaryOp::LogicalNot));
                 push!(Instruction::Jump {
                     class: JumpClass::IfFalse,
                     target
                 })
             }
-            (77, delta) => {
-                let target = instruction_count + 1 + extend_arg!(*delta);
-                if target as usize >= code.len() {
-                    return Err(IRParseError::JumpPastEnd(target));
-                }
+            Opcode::JumpForward => {
+                let target = jump_target()?;
                 push!(Instruction::Jump {
                     class: JumpClass::Always,
                     target
                 })
             }
-            (75, delta) => {
-                let arg = extend_arg!(*delta);
-                let Some(target) = (instruction_count + 1).checked_sub(arg) else {
-                    return Err(IRParseError::JumpBeforeStart(arg - instruction_count - 1));
-                };
+            Opcode::JumpBackward | Opcode::JumpBackwardNoInterrupt | Opcode::JumpAbsolute => {
+                let target = jump_target()?;
                 push!(Instruction::Jump {
                     class: JumpClass::Always,
                     target
                 })
             }
 
+            // Jumps that keep the condition on the stack if they're taken, and
+            // pop it otherwise, until 3.12
+            Opcode::JumpIfFalseOrPop | Opcode::JumpIfTrueOrPop => {
+                let target = jump_target()?;
+                push!(Instruction::Copy(1));
+                if raw.opcode == Opcode::JumpIfTrueOrPop {
+                    push!(Instruction::UnaryOp(UnaryOp::LogicalNot));
+                }
+                push!(Instruction::Jump {
+                    class: JumpClass::IfFalse,
+                    target
+                });
+                push!(Instruction::Pop)
+            }
+
+            // Iteration
+            Opcode::ForIter => {
+                let exit_target = jump_target()?;
+                if version < PythonVersion::V3_14 {
+                    popping_for_iters.push(out.len());
+                }
+                push!(Instruction::ForIter { exit_target })
+            }
+            // The async iterator is left below the awaitable
+            Opcode::GetAnext => {
+                push!(Instruction::Copy(1));
+                push!(Instruction::Coercion(Coercion::AsyncNext))
+            }
+            // Pops the StopAsyncIteration exception and the async iterator
+            Opcode::EndAsyncFor => {
+                push!(Instruction::Pop);
+                push!(Instruction::PopIter)
+            }
+
             // Call
-            (52, n) => {
-                push!(Instruction::Call(extend_arg!(*n)))
+            Opcode::Call => push!(Instruction::Call {
+                count: arg,
+                receiver: true,
+                keywords: std::mem::take(&mut kw_names)
+            }),
+            Opcode::KwNames => {
+                kw_names = true;
+                push!(Instruction::LoadConst(Constant::ByIndex(arg)))
             }
+            // PRECALL is always followed by CALL in 3.11
+            Opcode::Precall => {}
+            Opcode::CallFunction => push!(Instruction::Call {
+                count: arg,
+                receiver: false,
+                keywords: false
+            }),
+            Opcode::CallFunctionKw => push!(Instruction::Call {
+                count: arg,
+                receiver: false,
+                keywords: true
+            }),
+            Opcode::CallMethod => push!(Instruction::Call {
+                count: arg,
+                receiver: true,
+                keywords: false
+            }),
+            Opcode::CallKw => push!(Instruction::Call {
+                count: arg,
+                receiver: true,
+                keywords: true
+            }),
+            // Since 3.14 NULL is pushed when there aren't keyword arguments,
+            // before that the low bit is set if there are any
+            Opcode::CallFunctionEx => push!(Instruction::CallEx {
+                receiver: version >= PythonVersion::V3_11,
+                kwargs: version >= PythonVersion::V3_14 || arg & 1 != 0
+            }),
+            Opcode::ListToTuple => push!(Instruction::CallIntrinsic1(Intrinsic1::ListToTuple)),
+            Opcode::AsyncGenWrap => push!(Instruction::CallIntrinsic1(Intrinsic1::AsyncGenWrap)),
+            Opcode::CallIntrinsic1 => push!(match arg {
+                1 => Instruction::CallIntrinsic1(Intrinsic1::Print),
+                2 => Instruction::CallIntrinsic1(Intrinsic1::ImportStar),
+                3 => Instruction::CallIntrinsic1(Intrinsic1::StopIterationError),
+                4 => Instruction::CallIntrinsic1(Intrinsic1::AsyncGenWrap),
+                5 => Instruction::UnaryOp(UnaryOp::Positive),
+                6 => Instruction::CallIntrinsic1(Intrinsic1::ListToTuple),
+                7 => Instruction::CallIntrinsic1(Intrinsic1::TypeVar),
+                8 => Instruction::CallIntrinsic1(Intrinsic1::ParamSpec),
+                9 => Instruction::CallIntrinsic1(Intrinsic1::TypeVarTuple),
+                10 => Instruction::CallIntrinsic1(Intrinsic1::SubscriptGeneric),
+                11 => Instruction::CallIntrinsic1(Intrinsic1::TypeAlias),
+                n => return Err(IRParseError::OutOfBoundsIntrinsic(n)),
+            }),
+            Opcode::CallIntrinsic2 => push!(Instruction::CallIntrinsic2(match arg {
+                1 => Intrinsic2::PrepReraiseStar,
+                2 => Intrinsic2::TypeVarWithBound,
+                3 => Intrinsic2::TypeVarWithConstraints,
+                4 => Intrinsic2::SetFunctionTypeParams,
+                5 => Intrinsic2::SetTypeParamDefault,
+                n => return Err(IRParseError::OutOfBoundsIntrinsic(n)),
+            })),
 
             // Return
-            (35, _) => {
-                arg_extension = 0;
+            Opcode::ReturnValue => {
                 push!(Instruction::Return)
             }
+            Opcode::ReturnConst => {
+                push!(Instruction::LoadConst(Constant::ByIndex(arg)));
+                push!(Instruction::Return)
+            }
+            Opcode::ReturnGenerator => {}
+
+            // Generators
+            Opcode::YieldValue => push!(Instruction::Yield),
+            // The yields in the loop until the target are skipped over. Before
+            // 3.12 it pops the receiver itself instead of leaving it to END_SEND
+            Opcode::Send => {
+                let target = jump_target()?;
+                push!(Instruction::YieldFrom);
+                if version < PythonVersion::V3_12 {
+                    push!(Instruction::EndSend);
+                }
+                push!(Instruction::Jump {
+                    class: JumpClass::Always,
+                    target
+                })
+            }
+            Opcode::EndSend => push!(Instruction::EndSend),
+            Opcode::CleanupThrow => push!(Instruction::CleanupThrow),
+            Opcode::GetYieldFromIter => {
+                push!(Instruction::Coercion(Coercion::YieldFromIter))
+            }
+
+            // Exceptions, the lasti below the exception isn't modelled
+            Opcode::Reraise => push!(Instruction::Reraise),
+            Opcode::RaiseVarargs => push!(Instruction::Raise { count: arg }),
+            Opcode::PushExcInfo => push!(Instruction::PushExcInfo),
+            Opcode::PopExcept => push!(Instruction::PopExcept),
+            Opcode::CheckExcMatch => push!(Instruction::CheckExcMatch),
+            Opcode::CheckEgMatch => push!(Instruction::CheckEgMatch),
+            // CALL_INTRINSIC_2 from 3.12
+            Opcode::PrepReraiseStar => {
+                push!(Instruction::CallIntrinsic2(Intrinsic2::PrepReraiseStar))
+            }
+
+            // With statements
+            Opcode::LoadSpecial => push!(Instruction::LoadSpecial(match arg {
+                0 => SpecialMethod::Enter,
+                1 => SpecialMethod::Exit,
+                2 => SpecialMethod::AsyncEnter,
+                3 => SpecialMethod::AsyncExit,
+                n => return Err(IRParseError::OutOfBoundsSpecialMethod(n)),
+            })),
+            Opcode::WithExceptStart => push!(Instruction::WithExceptStart {
+                exit_depth: match version < PythonVersion::V3_14 {
+                    true => 4,
+                    false => 5,
+                }
+            }),
+            // Loads `__exit__` and calls `__enter__` like the LOAD_SPECIAL
+            // sequence of 3.14, except that only the bound exit method is kept
+            Opcode::BeforeWith | Opcode::BeforeAsyncWith => {
+                let (enter, exit) = match raw.opcode {
+                    Opcode::BeforeWith => (SpecialMethod::Enter, SpecialMethod::Exit),
+                    _ => (SpecialMethod::AsyncEnter, SpecialMethod::AsyncExit),
+                };
+                push!(Instruction::Copy(1));
+                push!(Instruction::LoadSpecial(exit));
+                push!(Instruction::Pop);
+                push!(Instruction::Swap(2));
+                push!(Instruction::LoadSpecial(enter));
+                push!(Instruction::Call {
+                    count: 0,
+                    receiver: true,
+                    keywords: false
+                })
+            }
 
             // Coercions
-            (39, _) => {
-                arg_extension = 0;
+            Opcode::ToBool => {
                 push!(Instruction::Coercion(Coercion::Bool))
             }
-            (16, _) => {
-                arg_extension = 0;
+            Opcode::GetIter => {
                 push!(Instruction::Coercion(Coercion::Iter))
             }
-            (71, _) => {
-                arg_extension = 0;
+            Opcode::GetAwaitable => {
                 push!(Instruction::Coercion(Coercion::Awaitable))
             }
-            (14, _) => {
-                arg_extension = 0;
+            Opcode::GetAiter => {
                 push!(Instruction::Coercion(Coercion::AsyncIter))
             }
 
+            // Pattern matching
+            Opcode::MatchClass => push!(Instruction::MatchClass { count: arg }),
+            Opcode::MatchMapping => push!(Instruction::MatchMapping),
+            Opcode::MatchSequence => push!(Instruction::MatchSequence),
+            Opcode::MatchKeys => push!(Instruction::MatchKeys),
+            Opcode::GetLen => push!(Instruction::GetLen),
+
+            // f-strings, the pieces are concatenated by BUILD_STRING
+            Opcode::ConvertValue => match conversion(arg)? {
+                Some(conversion) => push!(Instruction::ConvertValue(conversion)),
+                None => return Err(IRParseError::OutOfBoundsConversion(arg)),
+            },
+            Opcode::FormatSimple => push!(Instruction::FormatValue { spec: false }),
+            Opcode::FormatWithSpec => push!(Instruction::FormatValue { spec: true }),
+            // The low bit is whether there is a spec, and the bits from the
+            // third up are the conversion
+            Opcode::BuildInterpolation => push!(Instruction::BuildInterpolation {
+                conversion: conversion(arg >> 2)?,
+                spec: arg & 1 != 0,
+            }),
+            Opcode::BuildTemplate => push!(Instruction::BuildTemplate),
+            // The low bits are the conversion, and the third whether there is a
+            // spec on top of the value, until 3.13
+            Opcode::FormatValue => {
+                let spec = arg & 4 != 0;
+                if let Some(conversion) = conversion(arg & 3)? {
+                    if spec {
+                        push!(Instruction::Swap(2));
+                    }
+                    push!(Instruction::ConvertValue(conversion));
+                    if spec {
+                        push!(Instruction::Swap(2));
+                    }
+                }
+                push!(Instruction::FormatValue { spec })
+            }
+
             // Make Function
-            (23, _) => {
-                arg_extension = 0;
+            Opcode::MakeFunction if version >= PythonVersion::V3_13 => {
                 push!(Instruction::MakeFunction)
             }
-
-            // Extend args
-            (69, n) => {
-                if arg_extension > ((1 << 24) - 1) {
-                    return Err(IRParseError::ArgExtendWouldOverflow(arg_extension));
+            // Until 3.13 the argument is the attributes that are on the stack
+            // below the code, in the order of their bits, and until 3.11 the
+            // qualified name is on top of the code
+            Opcode::MakeFunction => {
+                if version < PythonVersion::V3_11 {
+                    let Some(Instruction::LoadConst(_)) = out.last() else {
+                        return Err(IRParseError::NonConstantQualname(raw.offset));
+                    };
+                    out.pop();
+                    origins.pop();
+                }
+                push!(Instruction::MakeFunction);
+                for (bit, attribute) in [
+                    (8, FunctionAttribute::Closure),
+                    (4, FunctionAttribute::Annotations),
+                    (2, FunctionAttribute::KwDefaults),
+                    (1, FunctionAttribute::Defaults),
+                ] {
+                    if arg & bit != 0 {
+                        push!(Instruction::SetFunctionAttribute(attribute))
+                    }
+                }
+                if arg > 15 {
+                    return Err(IRParseError::OutOfBoundsFunctionAttribute(arg));
                 }
-                arg_extension += *n as u32;
-                arg_extension <<= 8;
             }
+            Opcode::SetFunctionAttribute => push!(Instruction::SetFunctionAttribute(match arg {
+                1 => FunctionAttribute::Defaults,
+                2 => FunctionAttribute::KwDefaults,
+                4 => FunctionAttribute::Annotations,
+                8 => FunctionAttribute::Closure,
+                16 => FunctionAttribute::Annotate,
+                n => return Err(IRParseError::OutOfBoundsFunctionAttribute(n)),
+            })),
 
             // NOPs
-            (27 | 0 | 128 | 28, _) => {
-                arg_extension = 0;
-            }
+            // Cells are made and free variables are copied into the frame
+            // before anything runs
+            Opcode::Nop
+            | Opcode::Resume
+            | Opcode::NotTaken
+            | Opcode::CopyFreeVars
+            | Opcode::MakeCell => {}
+            // Caches are skipped by the decoder, so this isn't after an opcode
+            // that has caches
+            Opcode::Cache => return Err(IRParseError::UnexpectedCache(raw.offset)),
 
-            (op, _) => return Err(IRParseError::NotYetImplementedInstruction(*op)),
+            op => return Err(IRParseError::NotYetImplementedInstruction(op)),
         };
-        instruction_count += 1;
     }
 
+    // The index of the first instruction lowered from the opcode at this
+    // offset, or from the first one after it
+    let index =
+        |offset: usize| origins.partition_point(|origin: &Origin| origin.offset < offset) as u32;
+
     // Patch the jumps to point to the new correct place
     for instr in out.iter_mut() {
-        let Instruction::Jump { class: _, target } = instr else {
+        let (Instruction::Jump { class: _, target }
+        | Instruction::ForIter {
+            exit_target: target,
+        }) = instr
+        else {
             continue;
         };
-        let new_target = mapping.partition_point(|x| x < target);
-        if new_target == mapping.len() {
+        let new_target = index(2 * *target as usize);
+        if new_target as usize == origins.len() {
             panic!(
-                "Found an out of bounds jump in the patching step, which indicates a bug. target = {target}, new_target = {new_target}, mapping: {mapping:?}"
+                "Found an out of bounds jump in the patching step, which indicates a bug. target = {target}, new_target = {new_target}, origins: {origins:?}"
             );
         }
 
-        *target = new_target as u32
+        *target = new_target
+    }
+
+    // Handlers can only start at instructions, but their ranges can end at
+    // the end of the code
+    let mut exception_handlers = Vec::new();
+    for entry in parse_exception_table(exception_table) {
+        let target = index(entry.target);
+        if target as usize == out.len() {
+            return Err(IRParseError::HandlerPastEnd(entry.target));
+        }
+        exception_handlers.push(ExceptionHandler {
+            start: index(entry.start),
+            end: index(entry.end),
+            target,
+            depth: entry.depth,
+            lasti: entry.lasti,
+        });
+    }
+
+    // The iterator is left on the stack for the exit of a loop since 3.14, so
+    // older FOR_ITERs exit through a PopIter on the way to their target. These
+    // go after everything else so they don't move any of the other indices
+    for for_iter in popping_for_iters {
+        let pop_iter = out.len() as u32;
+        let Instruction::ForIter { exit_target } = &mut out[for_iter] else {
+            unreachable!()
+        };
+        let target = std::mem::replace(exit_target, pop_iter);
+        out.push(Instruction::PopIter);
+        out.push(Instruction::Jump {
+            class: JumpClass::Always,
+            target,
+        });
+        origins.extend([origins[for_iter]; 2]);
     }
 
-    Ok(out)
+    Ok(ParsedCode {
+        instructions: out,
+        exception_handlers,
+        origins,
+    })
+}
+
+/// The comparison of `COMPARE_OP`, after the bits around it are removed
+fn comparison(arg: u32) -> Option<BinOp> {
+    Some(match arg {
+        0 => BinOp::Lt,
+        1 => BinOp::LtEq,
+        2 => BinOp::Eq,
+        3 => BinOp::Ne,
+        4 => BinOp::Gt,
+        5 => BinOp::GtEq,
+        _ => return None,
+    })
 }
 
-fn as_tuple(code: &[u8]) -> &[(u8, u8)] {
-    assert!(
-        code.len() % 2 == 0,
-        "Since 3.6 code byte strings should be pairs of (instruction, opcode) bytes, and have even length"
-    );
-    // SAFETY: This is safe if we know that code.len is even. This is because
-    // any two u8 adjacent forms a valid (u8, u8), and because the new slice is
-    // not out of bounds of the original allocation (it's length in bytes, and
-    // start address is the same)
-    unsafe { std::slice::from_raw_parts(code.as_ptr() as *const (u8, u8), code.len() / 2) }
+/// The conversion of an f-string field, where 0 is no conversion
+fn conversion(arg: u32) -> Result<Option<Conversion>, IRParseError> {
+    Ok(match arg {
+        0 => None,
+        1 => Some(Conversion::Str),
+        2 => Some(Conversion::Repr),
+        3 => Some(Conversion::Ascii),
+        n => return Err(IRParseError::OutOfBoundsConversion(n)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::OpcodeTable;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    /// Test that jump targets skip the inline caches of the jump
+    fn parse314_jump_backward_over_cache() {
+        let op = |opcode| {
+            OpcodeTable::for_version(PythonVersion::V3_14)
+                .find(opcode)
+                .unwrap()
+                .byte
+        };
+        #[rustfmt::skip]
+        let code = [
+            op(Opcode::LoadSmallInt), 1,
+            op(Opcode::PopTop), 0,
+            op(Opcode::JumpBackward), 3, 0, 0,
+        ];
+        let parsed = parse314(&code, &[]).unwrap().instructions;
+        assert!(
+            matches!(
+                parsed.as_slice(),
+                [
+                    Instruction::LoadConst(Constant::SmallInt(1)),
+                    Instruction::Pop,
+                    Instruction::Jump {
+                        class: JumpClass::Always,
+                        target: 1
+                    }
+                ]
+            ),
+            "{parsed:?}"
+        );
+    }
+
+    #[test]
+    /// Test that the method bit is split off attribute name indices
+    fn parse314_attributes() {
+        let table = OpcodeTable::for_version(PythonVersion::V3_14);
+        let mut code = Vec::new();
+        for (opcode, arg) in [
+            (Opcode::LoadFast, 0),
+            (Opcode::LoadAttr, 3),
+            (Opcode::StoreAttr, 4),
+            (Opcode::LoadSuperAttr, 4 << 2 | 2 | 1),
+            (Opcode::DeleteSubscr, 0),
+        ] {
+            let info = table.find(opcode).unwrap();
+            code.extend([info.byte, arg]);
+            code.resize(code.len() + 2 * info.cache_entries as usize, 0);
+        }
+        let parsed = parse314(&code, &[]).unwrap().instructions;
+        assert!(
+            matches!(
+                parsed.as_slice(),
+                [
+                    Instruction::Load {
+                        from: UnresolvedPlace::Local(0)
+                    },
+                    Instruction::LoadAttr {
+                        name: 1,
+                        method: true
+                    },
+                    Instruction::StoreAttr { name: 4 },
+                    Instruction::LoadSuperAttr {
+                        name: 4,
+                        method: true
+                    },
+                    Instruction::Delete {
+                        place: DeletePlace::Subscript
+                    },
+                ]
+            ),
+            "{parsed:?}"
+        );
+    }
+
+    #[test]
+    fn parse314_deletes() {
+        let table = OpcodeTable::for_version(PythonVersion::V3_14);
+        let mut code = Vec::new();
+        for (opcode, arg) in [
+            (Opcode::DeleteFast, 0),
+            (Opcode::DeleteGlobal, 1),
+            (Opcode::DeleteName, 2),
+            (Opcode::DeleteDeref, 3),
+            (Opcode::DeleteAttr, 4),
+            (Opcode::DeleteSubscr, 0),
+        ] {
+            code.extend([table.find(opcode).unwrap().byte, arg]);
+        }
+        let parsed = parse314(&code, &[]).unwrap().instructions;
+        assert_eq!(
+            parsed,
+            [
+                DeletePlace::Variable(UnresolvedPlace::Local(0)),
+                DeletePlace::Variable(UnresolvedPlace::Global(1)),
+                DeletePlace::Variable(UnresolvedPlace::Name(2)),
+                DeletePlace::Variable(UnresolvedPlace::Cell(3)),
+                DeletePlace::Attribute(4),
+                DeletePlace::Subscript,
+            ]
+            .map(|place| Instruction::Delete { place })
+        );
+    }
+
+    #[test]
+    fn parse314_common_constants() {
+        let table = OpcodeTable::for_version(PythonVersion::V3_14);
+        let load = table.find(Opcode::LoadCommonConstant).unwrap().byte;
+        let code = (0..5).flat_map(|arg| [load, arg]).collect::<Vec<_>>();
+        let parsed = parse314(&code, &[]).unwrap().instructions;
+        assert_eq!(
+            parsed,
+            [
+                CommonConstant::AssertionError,
+                CommonConstant::NotImplementedError,
+                CommonConstant::Tuple,
+                CommonConstant::All,
+                CommonConstant::Any,
+            ]
+            .map(|constant| Instruction::LoadConst(Constant::Common(constant)))
+        );
+        assert!(matches!(
+            parse314(&[load, 5], &[]),
+            Err(IRParseError::OutOfBoundsCommonConstant(5))
+        ));
+    }
+
+    #[test]
+    /// Test `scale` from examples/dis/sample.py in every version before 3.14,
+    /// which has comparisons, arithmetic and a loop whose FOR_ITER pops the
+    /// iterator
+    fn parse_scale_every_version() {
+        type Parse = fn(&[u8]) -> Result<ParsedCode, IRParseError>;
+        let versions: [(Parse, &[u8]); 6] = [
+            (
+                parse38,
+                b"g\x00}\x03|\x00D\x00](}\x04|\x04d\x01k\x04r\x1e|\x04d\x02k\x07r\x1eq\x08|\x03\xa0\x00|\x04|\x01\x14\x00|\x02\x17\x00\xa1\x01\x01\x00q\x08|\x03S\x00",
+            ),
+            (
+                parse39,
+                b"g\x00}\x03|\x00D\x00](}\x04|\x04d\x01k\x04r\x1e|\x04d\x02v\x01r\x1eq\x08|\x03\xa0\x00|\x04|\x01\x14\x00|\x02\x17\x00\xa1\x01\x01\x00q\x08|\x03S\x00",
+            ),
+            (
+                parse310,
+                b"g\x00}\x03|\x00D\x00]\x14}\x04|\x04d\x01k\x04r\x0f|\x04d\x02v\x01r\x0fq\x04|\x03\xa0\x00|\x04|\x01\x14\x00|\x02\x17\x00\xa1\x01\x01\x00q\x04|\x03S\x00",
+            ),
+            (
+                |code| parse311(code, &[]),
+                b"\x97\x00g\x00}\x03|\x00D\x00](}\x04|\x04d\x01k\x04\x00\x00\x00\x00r\x05|\x04d\x02v\x01r\x01\x8c\r|\x03\xa0\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00|\x04|\x01z\x05\x00\x00|\x02z\x00\x00\x00\xa6\x01\x00\x00\xab\x01\x00\x00\x00\x00\x00\x00\x00\x00\x01\x00\x8c)|\x03S\x00",
+            ),
+            (
+                |code| parse312(code, &[]),
+                b"\x97\x00g\x00}\x03|\x00D\x00]#\x00\x00}\x04|\x04d\x01kD\x00\x00r\x05|\x04d\x02v\x01r\x01\x8c\r|\x03j\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00|\x04|\x01z\x05\x00\x00|\x02z\x00\x00\x00\xab\x01\x00\x00\x00\x00\x00\x00\x01\x00\x8c%\x04\x00|\x03S\x00",
+            ),
+            (
+                |code| parse313(code, &[]),
+                b"\x95\x00/\x00n\x03U\x00\x13\x00H'\x00\x00n\x04U\x04S\x01:\x94\x00\x00a\x08\x00\x00U\x04S\x02;\x01\x00\x00a\x02\x00\x00M\x11\x00\x00U\x03R\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00XA-\x05\x00\x00U\x02-\x00\x00\x005\x01\x00\x00\x00\x00\x00\x00 \x00M)\x00\x00\x0b\x00 \x00U\x03$\x00",
+            ),
+        ];
+        for (i, (parse, code)) in versions.into_iter().enumerate() {
+            let parsed = parse(code).unwrap().instructions;
+            let ops: Vec<_> = parsed
+                .iter()
+                .filter_map(|instruction| match instruction {
+                    Instruction::BinaryOp(op) => Some(*op),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(
+                ops,
+                [BinOp::Gt, BinOp::NotIn, BinOp::Mul, BinOp::Add],
+                "version {i}"
+            );
+
+            // The loop exits through a PopIter to `return out`
+            let Some(Instruction::ForIter { exit_target }) = parsed
+                .iter()
+                .find(|instruction| matches!(instruction, Instruction::ForIter { .. }))
+            else {
+                panic!("Expected a loop, got {parsed:?}")
+            };
+            let [Instruction::PopIter, Instruction::Jump { target, .. }] =
+                &parsed[*exit_target as usize..]
+            else {
+                panic!("Expected the exit to pop the iterator, got {parsed:?}")
+            };
+            assert_eq!(
+                parsed[*target as usize..*target as usize + 2],
+                [
+                    Instruction::Load {
+                        from: UnresolvedPlace::Local(3)
+                    },
+                    Instruction::Return
+                ],
+                "version {i}"
+            );
+        }
+    }
+
+    #[test]
+    /// Test `def checked(x): assert x, {"x": x, "n": 1}` in every version from
+    /// 3.9, which raises an AssertionError with a dict of constant keys
+    fn parse_assert_every_version() {
+        type Parse = fn(&[u8]) -> Result<ParsedCode, IRParseError>;
+        let versions: [(Parse, &[u8]); 5] = [
+            (
+                parse39,
+                b"|\x00s\x12J\x00|\x00d\x01d\x02\x9c\x02\x83\x01\x82\x01d\x00S\x00",
+            ),
+            (
+                parse310,
+                b"|\x00s\tJ\x00|\x00d\x01d\x02\x9c\x02\x83\x01\x82\x01d\x00S\x00",
+            ),
+            (
+                |code| parse311(code, &[]),
+                b"\x97\x00|\x00s\rJ\x00|\x00d\x01d\x02\x9c\x02\xa6\x00\x00\x00\xab\x00\x00\x00\x00\x00\x00\x00\x00\x00\x82\x01d\x00S\x00",
+            ),
+            (
+                |code| parse312(code, &[]),
+                b"\x97\x00|\x00s\nJ\x00|\x00d\x01d\x02\x9c\x02\xab\x00\x00\x00\x00\x00\x00\x00\x82\x01y\x00",
+            ),
+            (
+                |code| parse313(code, &[]),
+                b"\x95\x00U\x00(\x00\x00\x00\x00\x00\x00\x00d\n\x00\x00\x17\x00U\x00S\x01S\x02.\x025\x00\x00\x00\x00\x00\x00\x00e\x01g\x00",
+            ),
+        ];
+        let expected = [
+            Instruction::LoadConst(Constant::Common(CommonConstant::AssertionError)),
+            Instruction::Load {
+                from: UnresolvedPlace::Local(0),
+            },
+            Instruction::LoadConst(Constant::ByIndex(1)),
+            Instruction::LoadConst(Constant::ByIndex(2)),
+            Instruction::BuildConstKeyMap { count: 2 },
+        ];
+        for (i, (parse, code)) in versions.into_iter().enumerate() {
+            let parsed = parse(code).unwrap().instructions;
+            assert!(
+                parsed
+                    .windows(expected.len())
+                    .any(|window| window == expected),
+                "version {i}: {parsed:?}"
+            );
+        }
+    }
+
+    #[test]
+    /// Test that the argument of IS_OP and CONTAINS_OP inverts them
+    fn parse314_inverted_ops() {
+        let table = OpcodeTable::for_version(PythonVersion::V3_14);
+        let mut code = Vec::new();
+        for (opcode, arg) in [
+            (Opcode::IsOp, 0),
+            (Opcode::IsOp, 1),
+            (Opcode::ContainsOp, 0),
+            (Opcode::ContainsOp, 1),
+        ] {
+            let info = table.find(opcode).unwrap();
+            code.extend([info.byte, arg]);
+            code.resize(code.len() + 2 * info.cache_entries as usize, 0);
+        }
+        let parsed = parse314(&code, &[]).unwrap().instructions;
+        assert_eq!(
+            parsed,
+            [BinOp::Is, BinOp::IsNot, BinOp::In, BinOp::NotIn].map(Instruction::BinaryOp)
+        );
+    }
+
+    #[test]
+    /// Test that every instruction lowered from an opcode has its offset, which
+    /// is after any EXTENDED_ARGs
+    fn parse314_origins() {
+        let table = OpcodeTable::for_version(PythonVersion::V3_14);
+        let mut code = Vec::new();
+        for (opcode, arg) in [
+            (Opcode::ExtendedArg, 1),
+            (Opcode::LoadConst, 0),
+            (Opcode::PopJumpIfNone, 0),
+            (Opcode::Nop, 0),
+            (Opcode::ReturnValue, 0),
+        ] {
+            let info = table.find(opcode).unwrap();
+            code.extend([info.byte, arg]);
+            code.resize(code.len() + 2 * info.cache_entries as usize, 0);
+        }
+        let parsed = parse314(&code, &[]).unwrap();
+        let return_offset = code.len() - 2;
+        let origin = |offset, opcode| Origin { offset, opcode };
+        assert_eq!(
+            parsed.origins,
+            [
+                origin(2, Opcode::LoadConst),
+                origin(4, Opcode::PopJumpIfNone),
+                origin(4, Opcode::PopJumpIfNone),
+                origin(4, Opcode::PopJumpIfNone),
+                origin(4, Opcode::PopJumpIfNone),
+                origin(return_offset, Opcode::ReturnValue),
+            ]
+        );
+        assert_eq!(parsed.instructions.len(), parsed.origins.len());
+    }
+
+    #[test]
+    /// Test that LOAD_GLOBAL only pushes NULL when the low bit is set, and
+    /// pushes it after the global
+    fn parse314_load_global() {
+        let load_global = OpcodeTable::for_version(PythonVersion::V3_14)
+            .find(Opcode::LoadGlobal)
+            .unwrap();
+        let mut code = Vec::new();
+        for arg in [2, 5] {
+            code.extend([load_global.byte, arg]);
+            code.resize(code.len() + 2 * load_global.cache_entries as usize, 0);
+        }
+        let parsed = parse314(&code, &[]).unwrap().instructions;
+        assert!(
+            matches!(
+                parsed.as_slice(),
+                [
+                    Instruction::Load {
+                        from: UnresolvedPlace::Global(1)
+                    },
+                    Instruction::Load {
+                        from: UnresolvedPlace::Global(2)
+                    },
+                    Instruction::LoadConst(Constant::Null),
+                ]
+            ),
+            "{parsed:?}"
+        );
+    }
+
+    #[test]
+    /// Test that exhausted loops skip END_FOR, which isn't lowered
+    fn parse314_for_iter() {
+        let op = |opcode| {
+            OpcodeTable::for_version(PythonVersion::V3_14)
+                .find(opcode)
+                .unwrap()
+                .byte
+        };
+        #[rustfmt::skip]
+        let code = [
+            op(Opcode::LoadFast), 0,
+            op(Opcode::GetIter), 0,
+            op(Opcode::ForIter), 3, 0, 0,
+            op(Opcode::StoreFast), 1,
+            op(Opcode::JumpBackward), 5, 0, 0,
+            op(Opcode::EndFor), 0,
+            op(Opcode::PopIter), 0,
+            op(Opcode::LoadSmallInt), 0,
+            op(Opcode::ReturnValue), 0,
+        ];
+        let parsed = parse314(&code, &[]).unwrap().instructions;
+        assert!(
+            matches!(
+                parsed.as_slice(),
+                [
+                    Instruction::Load { .. },
+                    Instruction::Coercion(Coercion::Iter),
+                    Instruction::ForIter { exit_target: 5 },
+                    Instruction::Store { .. },
+                    Instruction::Jump {
+                        class: JumpClass::Always,
+                        target: 2
+                    },
+                    Instruction::PopIter,
+                    Instruction::LoadConst(Constant::SmallInt(0)),
+                    Instruction::Return,
+                ]
+            ),
+            "{parsed:?}"
+        );
+    }
 }
@@ -90,12 +90,15 @@ pub enum UnmarshalError {
 #[derive(Debug)]
 pub struct Unmarshaller<'a> {
     src: &'a [u8],
-    objects: Vec<PyObject>,
+    objects: Vec<PyObject<'a>>,
     refables: Vec<usize>,
 }
 
 impl<'a> Unmarshaller<'a> {
-    pub fn loads(src: &'a [u8]) -> Result<PyObjectRegion, UnmarshalError> {
+    /// Unmarshal the object at the start of `src`. Bytes and string payloads
+    /// are borrowed from `src` rather than copied, use
+    /// [`PyObjectRegion::into_owned`] if the region needs to outlive it
+    pub fn loads(src: &'a [u8]) -> Result<PyObjectRegion<'a>, UnmarshalError> {
         let mut this = Unmarshaller {
             src,
             objects: Vec::new(),
@@ -125,7 +128,7 @@ impl<'a> Unmarshaller<'a> {
         Ok(*b)
     }
 
-    fn get_short_str(&mut self) -> Result<&[u8], UnmarshalError> {
+    fn get_short_str(&mut self) -> Result<&'a [u8], UnmarshalError> {
         let len = self.get_byte()?;
         let Some(s) = self.src.split_off(..(len as usize)) else {
             return Err(UnmarshalError::UnexpectedEof);
@@ -133,7 +136,7 @@ impl<'a> Unmarshaller<'a> {
         Ok(s)
     }
 
-    fn get_str(&mut self) -> Result<&[u8], UnmarshalError> {
+    fn get_str(&mut self) -> Result<&'a [u8], UnmarshalError> {
         let len = u32::from_le_bytes(self.get_bytes()?);
         let Some(s) = self.src.split_off(..(len as usize)) else {
             return Err(UnmarshalError::UnexpectedEof);
@@ -158,13 +161,13 @@ impl<'a> Unmarshaller<'a> {
             PT::StopIter => PO::StopIter,
             PT::Ellipsis => PO::Ellipsis,
             PT::Int => PyObject::SmallInt(i32::from_le_bytes(self.get_bytes()?).into()),
-            PT::Int64 => PyObject::SmallInt(i64::from_le_bytes(self.get_bytes()?).into()),
+            PT::Int64 => PyObject::SmallInt(i64::from_le_bytes(self.get_bytes()?)),
             PT::Float => self.parse_fstr()?,
-            PT::BinaryFloat => PyObject::Float(f64::from_le_bytes(self.get_bytes()?).into()),
+            PT::BinaryFloat => PyObject::Float(f64::from_le_bytes(self.get_bytes()?)),
             PT::Complex => self.parse_cstr()?,
             PT::BinaryComplex => PyObject::Complex(
-                f64::from_le_bytes(self.get_bytes()?).into(),
-                f64::from_le_bytes(self.get_bytes()?).into(),
+                f64::from_le_bytes(self.get_bytes()?),
+                f64::from_le_bytes(self.get_bytes()?),
             ),
             PT::Long => PO::LargeInt(self.get_str()?.into()),
             PT::String => PO::Bytes(self.get_str()?.into()),
@@ -242,7 +245,7 @@ impl<'a> Unmarshaller<'a> {
         Ok(PyObjectIndex(idx))
     }
 
-    fn parse_str(&mut self) -> Result<PyObject, UnmarshalError> {
+    fn parse_str(&mut self) -> Result<PyObject<'a>, UnmarshalError> {
         let s = self.get_str()?;
         match str::from_utf8(s) {
             Ok(s) => Ok(PyObject::String(s.into())),
@@ -253,7 +256,7 @@ impl<'a> Unmarshaller<'a> {
     fn parse_sequence(
         &mut self,
         flag: bool,
-        constructor: fn(Box<[PyObjectIndex]>) -> PyObject,
+        constructor: fn(Box<[PyObjectIndex]>) -> PyObject<'a>,
     ) -> Result<PyObjectIndex, UnmarshalError> {
         let idx = self.objects.len();
         self.objects.push(PyObject::Null);
@@ -266,7 +269,7 @@ impl<'a> Unmarshaller<'a> {
         }
         let obj = constructor(self.parse_list(len as usize)?);
         self.objects[idx] = obj;
-        return Ok(PyObjectIndex(idx));
+        Ok(PyObjectIndex(idx))
     }
 
     fn parse_dict(&mut self, flag: bool) -> Result<PyObjectIndex, UnmarshalError> {
@@ -288,7 +291,7 @@ impl<'a> Unmarshaller<'a> {
         }
         let obj = PyObject::Dict(d.into_boxed_slice());
         self.objects[idx] = obj;
-        return Ok(PyObjectIndex(idx));
+        Ok(PyObjectIndex(idx))
     }
 
     fn parse_code(&mut self, flag: bool) -> Result<PyObjectIndex, UnmarshalError> {
@@ -319,7 +322,7 @@ impl<'a> Unmarshaller<'a> {
         Ok(PyObjectIndex(idx))
     }
 
-    fn parse_cstr(&mut self) -> Result<PyObject, UnmarshalError> {
+    fn parse_cstr(&mut self) -> Result<PyObject<'a>, UnmarshalError> {
         let Ok(s1) = str::from_utf8(self.get_short_str()?) else {
             return Err(UnmarshalError::DecodingError);
         };
@@ -336,7 +339,7 @@ impl<'a> Unmarshaller<'a> {
         }
     }
 
-    fn parse_fstr(&mut self) -> Result<PyObject, UnmarshalError> {
+    fn parse_fstr(&mut self) -> Result<PyObject<'a>, UnmarshalError> {
         let Ok(s) = str::from_utf8(self.get_short_str()?) else {
             return Err(UnmarshalError::DecodingError);
         };
@@ -361,6 +364,7 @@ impl<'a> Unmarshaller<'a> {
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
+    use std::borrow::Cow;
 
     #[test]
     /// This is a test that the tags can be converted losslessly between u8 and
@@ -656,7 +660,7 @@ mod tests {
 
         assert_eq!(
             objects.as_slice(),
-            &[PyObject::LargeInt(Box::new([0, 1]))],
+            &[PyObject::LargeInt([0, 1].as_slice().into())],
             "Incorrectly unmarshalled long [0,1]"
         )
     }
@@ -670,11 +674,38 @@ mod tests {
 
         assert_eq!(
             objects.as_slice(),
-            &[PyObject::Bytes(Box::new([0, 1, 1]))],
+            &[PyObject::Bytes([0, 1, 1].as_slice().into())],
             "Incorrectly unmarshalled bytes([0,1,1])"
         )
     }
 
+    #[test]
+    /// Test that bytes and string payloads borrow from the input rather than
+    /// being copied, and that the owned form is identical
+    fn unmarshal_payloads_are_borrowed() {
+        let src = b")\x02s\x03\x00\x00\x00\x00\x01\x01\xda\x03abc".to_vec();
+        let res = Unmarshaller::loads(&src);
+        let Ok(region) = res else {
+            panic!("Unmarshalling (bytes([0,1,1]), 'abc') failed, {res:?}");
+        };
+
+        let PyObjectRegion(objects) = &region;
+        assert!(matches!(objects[1], PyObject::Bytes(Cow::Borrowed(_))));
+        assert!(matches!(objects[2], PyObject::String(Cow::Borrowed(_))));
+
+        let owned = region.into_owned();
+        drop(src);
+        let PyObjectRegion(objects) = owned;
+        assert_eq!(
+            objects.as_slice(),
+            &[
+                PyObject::Tuple(Box::new([PyObjectIndex(1), PyObjectIndex(2)])),
+                PyObject::Bytes(Cow::Owned(vec![0, 1, 1])),
+                PyObject::String(Cow::Owned("abc".into())),
+            ]
+        )
+    }
+
     #[test]
     fn unmarshal_bytes_eof() {
         let res = Unmarshaller::loads(&[b's', 3, 0, 0, 0, 0, 1]);
@@ -1007,7 +1038,7 @@ mod tests {
                 PyObject::Tuple(Box::new([PyObjectIndex(3)])),
                 PyObject::SmallInt(5),
                 PyObject::Tuple(Box::new([])),
-                PyObject::Bytes(Box::new([])),
+                PyObject::Bytes([].as_slice().into()),
                 PyObject::String("example".into()),
                 PyObject::String("f".into()),
                 PyObject::Bytes(b"\x80\x00\xd9\x0b\x0c".as_slice().into()),
@@ -1055,11 +1086,11 @@ mod tests {
                 PyObject::Tuple(Box::new([])),
                 PyObject::Tuple(Box::new([PyObjectIndex(6)])),
                 PyObject::String("x".into()),
-                PyObject::Bytes(Box::new([0x26])),
+                PyObject::Bytes([0x26].as_slice().into()),
                 PyObject::String("example".into()),
                 PyObject::String("f".into()),
                 PyObject::Bytes(b"\x80\x00\xd8\x0b\x0c\x80H".as_slice().into()),
-                PyObject::Bytes(Box::new([])),
+                PyObject::Bytes([].as_slice().into()),
             ]
         )
     }
@@ -1111,7 +1142,7 @@ mod tests {
                 PyObject::Tuple(Box::new([PyObjectIndex(6), PyObjectIndex(7)])),
                 PyObject::String("y".into()),
                 PyObject::String("x".into()),
-                PyObject::Bytes(Box::new([0x26, 0x80])),
+                PyObject::Bytes([0x26, 0x80].as_slice().into()),
                 PyObject::String("example".into()),
                 PyObject::String("g".into()),
                 PyObject::String("f.<locals>.g".into()),
@@ -1120,7 +1151,7 @@ mod tests {
                         .as_slice()
                         .into()
                 ),
-                PyObject::Bytes(Box::new([])),
+                PyObject::Bytes([].as_slice().into()),
             ]
         )
     }
@@ -1196,7 +1227,7 @@ mod tests {
                 PyObject::Tuple(Box::new([PyObjectIndex(9), PyObjectIndex(10)])),
                 PyObject::String("y".into()),
                 PyObject::String("x".into()),
-                PyObject::Bytes(Box::new([0x26, 0x80])),
+                PyObject::Bytes([0x26, 0x80].as_slice().into()),
                 PyObject::String("example".into()),
                 PyObject::String("g".into()),
                 PyObject::String("f.<locals>.g".into()),
@@ -1205,9 +1236,9 @@ mod tests {
                         .as_slice()
                         .into()
                 ),
-                PyObject::Bytes(Box::new([])),
+                PyObject::Bytes([].as_slice().into()),
                 PyObject::Tuple(Box::new([PyObjectIndex(10), PyObjectIndex(13)])),
-                PyObject::Bytes(Box::new([0x66, 0x20])),
+                PyObject::Bytes([0x66, 0x20].as_slice().into()),
                 PyObject::String("f".into()),
                 PyObject::Bytes(
                     b"\xf8\x80\x00\xf5\x02\x01\x05\x13\xe0\x0b\x0c\x80H"
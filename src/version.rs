@@ -0,0 +1,117 @@
+//! The CPython versions that this crate knows the bytecode format of
+
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PythonVersion {
+    V3_8,
+    V3_9,
+    V3_10,
+    V3_11,
+    V3_12,
+    V3_13,
+    V3_14,
+}
+
+impl PythonVersion {
+    pub const ALL: [PythonVersion; 7] = [
+        PythonVersion::V3_8,
+        PythonVersion::V3_9,
+        PythonVersion::V3_10,
+        PythonVersion::V3_11,
+        PythonVersion::V3_12,
+        PythonVersion::V3_13,
+        PythonVersion::V3_14,
+    ];
+
+    /// The minor version number, e.g. 14 for 3.14
+    pub const fn minor(self) -> u8 {
+        match self {
+            PythonVersion::V3_8 => 8,
+            PythonVersion::V3_9 => 9,
+            PythonVersion::V3_10 => 10,
+            PythonVersion::V3_11 => 11,
+            PythonVersion::V3_12 => 12,
+            PythonVersion::V3_13 => 13,
+            PythonVersion::V3_14 => 14,
+        }
+    }
+
+    /// The version that writes `.pyc` files with this magic number. Each
+    /// version bumps the magic number within its own range during development
+    pub fn from_magic(magic: u16) -> Result<PythonVersion, VersionError> {
+        Ok(match magic {
+            3400..=3419 => PythonVersion::V3_8,
+            3420..=3429 => PythonVersion::V3_9,
+            3430..=3449 => PythonVersion::V3_10,
+            3450..=3499 => PythonVersion::V3_11,
+            3500..=3549 => PythonVersion::V3_12,
+            3550..=3599 => PythonVersion::V3_13,
+            3600..=3649 => PythonVersion::V3_14,
+            magic => return Err(VersionError::UnsupportedVersion(magic)),
+        })
+    }
+
+    /// The version of a `.pyc` file, from the magic number at its start. The
+    /// marshalled code object starts 16 bytes in
+    pub fn from_pyc(pyc: &[u8]) -> Result<PythonVersion, VersionError> {
+        match pyc {
+            [low, high, b'\r', b'\n', ..] => Self::from_magic(u16::from_le_bytes([*low, *high])),
+            _ => Err(VersionError::NotAPyc),
+        }
+    }
+
+    /// Returns true if jump arguments count code units (pairs of bytes), which
+    /// has been the case since 3.10. Before that they counted bytes
+    pub const fn jumps_in_code_units(self) -> bool {
+        self.minor() >= 10
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionError {
+    /// The magic number isn't one of a version this crate knows about
+    UnsupportedVersion(u16),
+    /// The file doesn't start with a magic number followed by `\r\n`
+    NotAPyc,
+}
+
+impl Display for PythonVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "3.{}", self.minor())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    /// Test the magic numbers of the final release of each version
+    fn version_from_magic() {
+        let magics = [3413, 3425, 3439, 3495, 3531, 3571, 3627];
+        for (magic, version) in magics.into_iter().zip(PythonVersion::ALL) {
+            assert_eq!(PythonVersion::from_magic(magic), Ok(version));
+        }
+        // 2.7 and 3.15
+        for magic in [62211, 3650] {
+            assert_eq!(
+                PythonVersion::from_magic(magic),
+                Err(VersionError::UnsupportedVersion(magic))
+            );
+        }
+    }
+
+    #[test]
+    fn version_from_pyc() {
+        assert_eq!(
+            PythonVersion::from_pyc(include_bytes!("../examples/initial.pyc")),
+            Ok(PythonVersion::V3_14)
+        );
+        assert_eq!(
+            PythonVersion::from_pyc(b"\x2b\x0e\r"),
+            Err(VersionError::NotAPyc)
+        );
+    }
+}